@@ -0,0 +1,268 @@
+use std::fmt::Write;
+
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::{
+    interactive::ShessInteractor,
+    shessboard::{
+        enums::{Color, GameEnd, Variant},
+        notation::Algebraic,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Seat {
+    Human,
+    /// Plays a random legal move.
+    Random,
+}
+
+impl Seat {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Seat::Human => "Human",
+            Seat::Random => "Random",
+        }
+    }
+}
+
+/// White on one board is partnered with Black on the other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoardId {
+    A,
+    B,
+}
+
+impl BoardId {
+    pub const fn index(&self) -> usize {
+        match self {
+            BoardId::A => 0,
+            BoardId::B => 1,
+        }
+    }
+
+    pub const fn other(&self) -> Self {
+        match self {
+            BoardId::A => BoardId::B,
+            BoardId::B => BoardId::A,
+        }
+    }
+
+    /// BPGN board letter, upper case for White and lower case for Black.
+    pub const fn letter(&self, color: Color) -> char {
+        match (self, color) {
+            (BoardId::A, Color::White) => 'A',
+            (BoardId::A, Color::Black) => 'a',
+            (BoardId::B, Color::White) => 'B',
+            (BoardId::B, Color::Black) => 'b',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BughouseMove {
+    pub board: BoardId,
+    pub color: Color,
+    pub turn: usize,
+    pub notation: (Algebraic, &'static str),
+}
+
+pub struct Bughouse {
+    pub boards: [ShessInteractor; 2],
+    /// Seats by board and then by color.
+    pub seats: [[Seat; 2]; 2],
+    pub log: Vec<BughouseMove>,
+    pub next: BoardId,
+}
+
+impl Bughouse {
+    pub fn new(seats: [[Seat; 2]; 2]) -> Self {
+        let mut boards = [ShessInteractor::new(), ShessInteractor::new()];
+        for b in &mut boards {
+            b.setup_variant(Variant::Bughouse);
+        }
+
+        Self {
+            boards,
+            seats,
+            log: Vec::new(),
+            next: BoardId::A,
+        }
+    }
+
+    pub fn board(&self, id: BoardId) -> &ShessInteractor {
+        &self.boards[id.index()]
+    }
+
+    pub fn seat(&self, id: BoardId) -> Seat {
+        let color = self.board(id).to_move();
+        self.seats[id.index()][color as usize - 1]
+    }
+
+    /// Plays a move on the given board and hands any captured piece to the
+    /// partner on the other board.
+    pub fn play(&mut self, id: BoardId, n: Algebraic) -> Result<BughouseMove, &'static str> {
+        let interactor = &mut self.boards[id.index()];
+        let color = interactor.to_move();
+        let turn = interactor.board.metadata.turn();
+
+        let pocketed = match n.find(&interactor.moves)[..] {
+            [mv] => interactor.board.pocketed(mv),
+            _ => None,
+        };
+        let (notation, _) = interactor.normal_move(n)?;
+
+        if let Some(p) = pocketed {
+            let partner = &mut self.boards[id.other().index()];
+            partner.board.metadata.pocket_mut(color.other()).add(p);
+            partner.recalc();
+        }
+
+        let mv = BughouseMove {
+            board: id,
+            color,
+            turn,
+            notation,
+        };
+        self.log.push(mv);
+        self.next = id.other();
+        Ok(mv)
+    }
+
+    pub fn random_move<R: Rng + ?Sized>(
+        &mut self,
+        id: BoardId,
+        rng: &mut R,
+    ) -> Result<BughouseMove, &'static str> {
+        let interactor = self.board(id);
+        let Some(mv) = interactor.moves.choose(rng) else {
+            return Err("No legal moves");
+        };
+        self.play(id, Algebraic::new(mv, &interactor.moves))
+    }
+
+    /// The end of the match as seen from board `A`: a win for White there is
+    /// shared with Black on board `B`.
+    pub fn victory(&self) -> Option<GameEnd> {
+        if let Some(end) = self.board(BoardId::A).victory() {
+            Some(end)
+        } else {
+            match self.board(BoardId::B).victory()? {
+                GameEnd::WhiteWins => Some(GameEnd::BlackWins),
+                GameEnd::BlackWins => Some(GameEnd::WhiteWins),
+                GameEnd::Draw => Some(GameEnd::Draw),
+            }
+        }
+    }
+
+    pub fn write_bpgn(&self) -> String {
+        let mut res = String::new();
+        let result = self.victory().map_or("*", |end| end.pgn_str());
+
+        writeln!(res, "[Event \"Bughouse\"]").unwrap();
+        for id in [BoardId::A, BoardId::B] {
+            for color in [Color::White, Color::Black] {
+                writeln!(
+                    res,
+                    "[{:?}{:?} \"{}\"]",
+                    color,
+                    id,
+                    self.seats[id.index()][color as usize - 1].name()
+                )
+                .unwrap();
+            }
+        }
+        writeln!(res, "[Result \"{result}\"]").unwrap();
+        res.push('\n');
+
+        for mv in &self.log {
+            let (n, suffix) = mv.notation;
+            write!(
+                res,
+                "{}{}. {}{} ",
+                mv.turn,
+                mv.board.letter(mv.color),
+                n,
+                suffix
+            )
+            .unwrap();
+        }
+        res.push_str(result);
+
+        res
+    }
+}
+
+#[test]
+fn captures_pass_to_partner() {
+    let mut bughouse = Bughouse::new([[Seat::Human; 2]; 2]);
+
+    for (id, s) in [
+        (BoardId::A, "e4"),
+        (BoardId::B, "e4"),
+        (BoardId::A, "d5"),
+        (BoardId::B, "d5"),
+        (BoardId::A, "exd5"),
+    ] {
+        let (n, "") = Algebraic::read(s).unwrap() else {
+            panic!()
+        };
+        bughouse.play(id, n).unwrap();
+    }
+
+    let b = &bughouse.board(BoardId::B).board.metadata;
+    assert_eq!(b.black_pocket.letters(Color::Black), "p");
+    assert!(b.white_pocket.is_empty());
+    assert!(
+        bughouse
+            .board(BoardId::A)
+            .board
+            .metadata
+            .white_pocket
+            .is_empty()
+    );
+
+    let (n, "") = Algebraic::read("P@e6").unwrap() else {
+        panic!()
+    };
+    assert_eq!(Algebraic::read("@e6"), Some((n, "")));
+    assert!(bughouse.play(BoardId::B, n).is_err());
+    bughouse
+        .play(BoardId::B, Algebraic::read("exd5").unwrap().0)
+        .unwrap();
+    bughouse.play(BoardId::B, n).unwrap();
+
+    assert_eq!(
+        bughouse.write_bpgn().lines().last().unwrap(),
+        "1A. e4 1B. e4 1a. d5 1b. d5 2A. exd5 2B. exd5 2b. P@e6 *"
+    );
+}
+
+#[test]
+fn random_bughouse_conserves_material() {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    let mut rng = StdRng::from_seed(*b"3.141592653589793238462643383279");
+
+    for _ in 0..10 {
+        let mut bughouse = Bughouse::new([[Seat::Random; 2]; 2]);
+
+        for _ in 0..300 {
+            if bughouse.victory().is_some() {
+                break;
+            }
+            bughouse.random_move(bughouse.next, &mut rng).unwrap();
+
+            let mut total = 0;
+            for b in &bughouse.boards {
+                let metadata = &b.board.metadata;
+                total += b.board.as_mask().occupied() as usize;
+                total += metadata.white_pocket.letters(Color::White).len();
+                total += metadata.black_pocket.letters(Color::Black).len();
+            }
+            assert_eq!(total, 64);
+        }
+
+        assert!(bughouse.write_bpgn().starts_with("[Event \"Bughouse\"]"));
+    }
+}
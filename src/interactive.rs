@@ -2,31 +2,70 @@ use crate::shessboard::{
     BitBoard,
     boardmap::BoardMap,
     castling::{CastlingInfo, CastlingRights},
-    enums::{Color, ColorPiece, GameEnd},
+    enums::{Color, ColorPiece, GameEnd, Variant},
     forced_draws::{LastChange, ThreefoldRule},
     masks::Mask,
     metadata::Metadata,
     moves::{Move, ProtoMove},
-    notation::Algebraic,
+    notation::{Algebraic, Played},
+    odds::Odds,
+    rules::Rules,
     squares::Square,
+    zobrist::{BitBoardHasher, HashResult},
 };
 
-pub struct ShessInteractor {
+pub struct ShessInteractor<R: Rules = Variant> {
+    pub rules: R,
     pub board: BitBoard,
     pub moves: Vec<Move>,
+    hasher: BitBoardHasher,
+    pub hash: HashResult,
+    /// When a capture or pawn move last happened in the game played so far.
+    pub last_change: LastChange<'static>,
+    /// Positions the game played so far has been through.
+    pub repetitions: ThreefoldRule<'static>,
+}
+
+impl Default for ShessInteractor {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ShessInteractor {
     pub fn new() -> Self {
+        Self::with_rules(Variant::Standard)
+    }
+
+    pub fn setup_variant(&mut self, variant: Variant) {
+        self.rules = variant;
+        self.setup();
+    }
+}
+
+impl<R: Rules> ShessInteractor<R> {
+    pub fn with_rules(rules: R) -> Self {
         Self {
+            rules,
             board: BitBoard::empty(),
             moves: Vec::with_capacity(50),
+            hasher: BitBoardHasher::new(),
+            hash: 0,
+            last_change: LastChange::start(),
+            repetitions: ThreefoldRule::empty(),
         }
     }
 
+    /// Starts the game history over from the current position.
+    fn restart_history(&mut self) {
+        self.hash = self.rules.hash(&self.hasher, &self.board);
+        self.last_change = LastChange::Static(self.board.metadata.tempo);
+        self.repetitions = ThreefoldRule::start(self.hash);
+    }
+
     pub fn recalc(&mut self) {
         self.moves.clear();
-        self.board.generate_moves(&mut self.moves);
+        self.rules.generate_moves(&self.board, &mut self.moves);
     }
 
     pub fn to_move(&self) -> Color {
@@ -34,9 +73,16 @@ impl ShessInteractor {
     }
 
     pub fn setup(&mut self) {
-        self.board = BitBoard::new();
-        self.moves.clear();
-        self.board.generate_moves(&mut self.moves);
+        self.board = self.rules.start();
+        self.restart_history();
+        self.recalc();
+    }
+
+    pub fn give_odds(&mut self, odds: &Odds) -> Result<(), &'static str> {
+        self.board.give_odds(odds, self.rules.royal())?;
+        self.restart_history();
+        self.recalc();
+        Ok(())
     }
 
     // pub fn set_position(&mut self, n: usize) {
@@ -55,11 +101,13 @@ impl ShessInteractor {
             ooo: false,
             oo: false,
         };
+        self.restart_history();
         self.moves.clear();
     }
 
     pub fn place(&mut self, p: Option<ColorPiece>, sq: Square) {
         self.board.set_piece(p, sq);
+        self.restart_history();
         self.recalc();
     }
 
@@ -78,7 +126,15 @@ impl ShessInteractor {
     }
 
     pub fn apply_move(&mut self, m: Move) {
-        self.board.apply(m);
+        self.hash = self
+            .rules
+            .hash_delta(&self.hasher, &self.board, self.hash, m);
+        self.rules.apply(&mut self.board, m);
+        self.last_change = self
+            .last_change
+            .see(self.board.metadata.tempo, m)
+            .collapse();
+        self.repetitions = self.repetitions.see(self.hash).collapse();
         self.recalc();
     }
 
@@ -87,30 +143,37 @@ impl ShessInteractor {
         n: Algebraic,
     ) -> Result<((Algebraic, &'static str), Move), &'static str> {
         let v = n.find(&self.moves);
-        let mv = if v.len() == 0 {
+        let mv = if v.is_empty() {
             return Err("No such legal move");
         } else if v.len() > 1 {
             return Err("Ambiguous move");
         } else {
-            v[0].clone()
+            v[0]
         };
 
-        self.board.apply(mv);
-        self.moves.clear();
-        self.board.generate_moves(&mut self.moves);
-        if self.board.is_in_check(self.to_move()) {
-            if self.moves.len() != 0 {
-                Ok(((n, "+"), mv))
-            } else {
-                Ok(((n, "#"), mv))
-            }
-        } else {
-            Ok(((n, ""), mv))
-        }
+        let suffix = self.rules.suffix(&self.board, mv);
+        self.apply_move(mv);
+        Ok(((n, suffix), mv))
+    }
+
+    /// Plays a move and records which turn it belonged to.
+    pub fn play(&mut self, n: Algebraic) -> Result<(Played, Move), &'static str> {
+        let turn = self.board.metadata.turn();
+        let color = self.to_move();
+        let (notation, mv) = self.normal_move(n)?;
+        Ok((
+            Played {
+                turn,
+                color,
+                notation,
+            },
+            mv,
+        ))
     }
 
     pub fn printable_metadata(&self) -> String {
         let metadata = &self.board.metadata;
+        let variant = metadata.variant.name();
         let to_move = metadata.to_move;
         let (wooo, woo) = castles(metadata.white_castling);
         let (booo, boo) = castles(metadata.black_castling);
@@ -121,12 +184,34 @@ impl ShessInteractor {
         let turn = metadata.turn();
         let (wooo, woo) = castles(metadata.white_castling);
         let (booo, boo) = castles(metadata.black_castling);
+        let checks = if metadata.variant == Variant::Marseillais {
+            let which = if metadata.sub_turn.second {
+                "second"
+            } else {
+                "first"
+            };
+            format!("\nMarseillais: {which} move of the turn")
+        } else if metadata.variant == Variant::ThreeCheck {
+            format!(
+                "\nChecks given: White {}, Black {}",
+                metadata.white_checks, metadata.black_checks
+            )
+        } else if metadata.variant.has_drops() {
+            format!(
+                "\nPockets: White [{}], Black [{}]",
+                metadata.white_pocket.letters(Color::White),
+                metadata.black_pocket.letters(Color::Black)
+            )
+        } else {
+            String::new()
+        };
 
         return format!(
-            "Turn: {turn}, {to_move:?} to move
+            "Variant: {variant}
+Turn: {turn}, {to_move:?} to move
 White castling righs: {wooo} K {woo}
 Black castling rights: {booo} K {boo}
-En passant square: {epc}",
+En passant square: {epc}{checks}",
         );
 
         fn castles(r: CastlingRights) -> (&'static str, &'static str) {
@@ -138,12 +223,12 @@ En passant square: {epc}",
     }
 
     pub fn victory(&self) -> Option<GameEnd> {
-        GameEnd::determine(
+        self.rules.game_end(
             &self.board,
             &self.moves,
-            0,
-            &LastChange::Static(0),
-            &ThreefoldRule::empty(),
+            self.hash,
+            &self.last_change,
+            &self.repetitions,
         )
     }
 
@@ -162,3 +247,22 @@ En passant square: {epc}",
         res
     }
 }
+
+#[test]
+fn repetitions_in_play() {
+    let mut interactor = ShessInteractor::new();
+    interactor.setup();
+
+    for _ in 0..2 {
+        for s in ["Nf3", "Nf6", "Ng1", "Ng8"] {
+            assert_eq!(interactor.victory(), None);
+            let (n, "") = Algebraic::read(s).unwrap() else {
+                panic!()
+            };
+            interactor.play(n).unwrap();
+        }
+    }
+
+    assert_eq!(interactor.victory(), Some(GameEnd::Draw));
+    assert_eq!(interactor.repetitions.count(interactor.hash), 3);
+}
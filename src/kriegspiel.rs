@@ -0,0 +1,263 @@
+use std::fmt::Display;
+
+use crate::{
+    interactive::ShessInteractor,
+    shessboard::{
+        boardmap::BoardMap,
+        enums::{Color, ColorPiece, GameEnd, Piece, Variant},
+        notation::Algebraic,
+        pieces::{bishops::Bishops, knights::Knights, pawns::Pawns, rooks::Rooks},
+        rules::Rules,
+        squares::Square,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckDirection {
+    File,
+    Rank,
+    LongDiagonal,
+    ShortDiagonal,
+    Knight,
+}
+
+impl CheckDirection {
+    /// Tells the two diagonals through `king` apart by their length.
+    fn diagonal(king: Square, checker: Square) -> Self {
+        let (kf, kr) = (king.file().as_file(), king.rank().as_rank());
+        let (cf, cr) = (checker.file().as_file(), checker.rank().as_rank());
+
+        let (on, other) = if kf - kr == cf - cr {
+            (8 - (kf - kr).abs(), 8 - (kf + kr - 7).abs())
+        } else {
+            (8 - (kf + kr - 7).abs(), 8 - (kf - kr).abs())
+        };
+
+        if on > other {
+            CheckDirection::LongDiagonal
+        } else {
+            CheckDirection::ShortDiagonal
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Announcement {
+    Illegal,
+    Capture(Square),
+    Check(CheckDirection),
+    /// Number of pawn captures available to the side to move.
+    PawnTries(usize),
+    GameOver(GameEnd),
+}
+
+impl Display for Announcement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Announcement::Illegal => write!(f, "illegal"),
+            Announcement::Capture(sq) => write!(f, "capture at {sq}"),
+            Announcement::Check(CheckDirection::File) => write!(f, "check on the file"),
+            Announcement::Check(CheckDirection::Rank) => write!(f, "check on the rank"),
+            Announcement::Check(CheckDirection::LongDiagonal) => {
+                write!(f, "check on the long diagonal")
+            }
+            Announcement::Check(CheckDirection::ShortDiagonal) => {
+                write!(f, "check on the short diagonal")
+            }
+            Announcement::Check(CheckDirection::Knight) => write!(f, "knight check"),
+            Announcement::PawnTries(1) => write!(f, "1 pawn try"),
+            Announcement::PawnTries(n) => write!(f, "{n} pawn tries"),
+            Announcement::GameOver(end) => write!(f, "game over {}", end.to_str()),
+        }
+    }
+}
+
+/// A move attempt in kriegspiel together with what the referee said.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attempt {
+    pub color: Color,
+    pub turn: usize,
+    pub notation: Algebraic,
+    pub announcements: Vec<Announcement>,
+}
+
+impl Display for Attempt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}. {:?} {}:", self.turn, self.color, self.notation)?;
+        for a in &self.announcements {
+            write!(f, " {a},")?;
+        }
+        Ok(())
+    }
+}
+
+/// A game with a referee between two players who only see their own pieces.
+pub struct Kriegspiel<R: Rules = Variant> {
+    pub interactor: ShessInteractor<R>,
+    /// Every attempt so far and what the referee said about it.
+    pub referee_log: Vec<Attempt>,
+}
+
+impl<R: Rules> Kriegspiel<R> {
+    pub fn new(rules: R) -> Self {
+        let mut interactor = ShessInteractor::with_rules(rules);
+        interactor.setup();
+        Self {
+            interactor,
+            referee_log: Vec::new(),
+        }
+    }
+
+    /// Plays a move as the referee. Illegal attempts leave the position
+    /// unchanged, and every attempt is kept in the referee log.
+    pub fn referee(&mut self, n: Algebraic) -> Vec<Announcement> {
+        let color = self.interactor.to_move();
+        let turn = self.interactor.board.metadata.turn();
+
+        let announcements = match self.interactor.normal_move(n) {
+            Err(_) => vec![Announcement::Illegal],
+            Ok((_, mv)) => {
+                let mut res = vec![];
+
+                if let Some((sq, _)) = mv.capture {
+                    res.push(Announcement::Capture(sq));
+                }
+                res.extend(self.checks(color).into_iter().map(Announcement::Check));
+
+                if let Some(end) = self.interactor.victory() {
+                    res.push(Announcement::GameOver(end));
+                } else {
+                    let tries = self
+                        .interactor
+                        .moves
+                        .iter()
+                        .filter(|m| m.color_and_piece.piece() == Piece::Pawn && m.capture.is_some())
+                        .count();
+                    if tries > 0 {
+                        res.push(Announcement::PawnTries(tries));
+                    }
+                }
+
+                res
+            }
+        };
+
+        self.referee_log.push(Attempt {
+            color,
+            turn,
+            notation: n,
+            announcements: announcements.clone(),
+        });
+
+        announcements
+    }
+
+    /// Directions from which `color` is checking the enemy king.
+    pub fn checks(&self, color: Color) -> Vec<CheckDirection> {
+        let board = &self.interactor.board;
+        let active = board.color(color);
+        let Some(king) = board.color(color.other()).kings.as_mask().first() else {
+            return vec![];
+        };
+        let blockers = board.as_mask();
+        let mut res = vec![];
+
+        let straight = Rooks::new(king.as_mask()).threats(blockers)
+            & (active.rooks.as_mask() | active.queens.as_mask());
+        for sq in straight {
+            res.push(if sq.file() == king.file() {
+                CheckDirection::File
+            } else {
+                CheckDirection::Rank
+            });
+        }
+
+        let diagonal = (Bishops::new(king.as_mask()).threats(blockers)
+            & (active.bishops.as_mask() | active.queens.as_mask()))
+            | (Pawns::new(king.as_mask()).threats(color.other()) & active.pawns.as_mask());
+        for sq in diagonal {
+            res.push(CheckDirection::diagonal(king, sq));
+        }
+
+        if (Knights::MOVES.at(king) & active.knights.as_mask()).any() {
+            res.push(CheckDirection::Knight);
+        }
+
+        res
+    }
+
+    /// The board as one kriegspiel player sees it, with only their own pieces.
+    pub fn own_pieces(&self, color: Color) -> BoardMap<Option<ColorPiece>> {
+        let mut res = BoardMap::new_with(None);
+        self.interactor.board.color(color).render(&mut res, color);
+        res
+    }
+
+    pub fn write_referee_log(&self) -> String {
+        self.referee_log
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+#[test]
+fn referee_announcements() {
+    let mut game = Kriegspiel::new(Variant::Standard);
+
+    for (s, announcements) in [
+        ("e4", vec![]),
+        ("Ke2", vec![Announcement::Illegal]),
+        ("d5", vec![Announcement::PawnTries(1)]),
+        ("exd5", vec![Announcement::Capture(Square::d5)]),
+        ("e5", vec![Announcement::PawnTries(1)]),
+        ("Qh5", vec![]),
+        ("Nc6", vec![Announcement::PawnTries(1)]),
+        ("Bb5", vec![]),
+        ("Nf6", vec![Announcement::PawnTries(1)]),
+        (
+            "Qxf7",
+            vec![
+                Announcement::Capture(Square::f7),
+                Announcement::Check(CheckDirection::ShortDiagonal),
+            ],
+        ),
+        (
+            "Kxf7",
+            vec![
+                Announcement::Capture(Square::f7),
+                Announcement::PawnTries(1),
+            ],
+        ),
+    ] {
+        let (n, "") = Algebraic::read(s).unwrap() else {
+            panic!()
+        };
+        assert_eq!(game.referee(n), announcements, "{s}");
+    }
+
+    assert_eq!(game.referee_log.len(), 11);
+    assert!(
+        game.write_referee_log()
+            .starts_with("1. White e4:\n1. Black Ke2: illegal,")
+    );
+
+    let interactor = &mut game.interactor;
+    interactor.reset();
+    interactor.place(Some(ColorPiece::BlackKing), Square::e8);
+    interactor.place(Some(ColorPiece::WhiteKing), Square::a1);
+    interactor.place(Some(ColorPiece::WhiteRook), Square::e2);
+    interactor.place(Some(ColorPiece::WhiteKnight), Square::d6);
+    interactor.place(Some(ColorPiece::WhiteBishop), Square::b5);
+    interactor.place(Some(ColorPiece::WhiteRook), Square::h8);
+    assert_eq!(
+        game.checks(Color::White),
+        vec![
+            CheckDirection::File,
+            CheckDirection::Rank,
+            CheckDirection::LongDiagonal,
+            CheckDirection::Knight
+        ]
+    );
+}
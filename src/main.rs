@@ -16,32 +16,74 @@ use rand::{
 };
 
 use crate::{
+    bughouse::{BoardId, Bughouse, Seat},
     interactive::ShessInteractor,
+    kriegspiel::{Announcement, Kriegspiel},
     shessboard::{
         BitBoard,
         boardmap::{BoardMap, BoardMapIter},
-        enums::{Color, ColorPiece, File, GameEnd, Piece, Rank, Shade},
+        enums::{Color, ColorPiece, File, GameEnd, Piece, Rank, Shade, Variant},
         forced_draws::{LastChange, ThreefoldRule},
         half::HalfBitBoard,
         masks::Mask,
         metadata::Metadata,
         moves::{Move, ProtoMove},
-        notation::{self, Algebraic},
+        notation::{self, Algebraic, Played, pgn::GameRecord, write_move_list},
+        odds::Odds,
         pieces::{
             bishops::Bishops, kings::Kings, knights::Knights, pawns::Pawns, queens::Queens,
             rooks::Rooks, slide_move_stop,
         },
+        rules::{Rules, StandardChess},
         squares::Square,
         zobrist::{BitBoardHasher, HashResult},
     },
+    shessboat::{
+        Minimax,
+        engine::Shessboat,
+        fog::fog_move,
+        time::{Clock, Limits},
+        write_pv, write_score,
+    },
 };
 
+pub mod bughouse;
 pub mod interactive;
+pub mod kriegspiel;
 pub mod shessboard;
 pub mod shessboat;
 
 fn main() {
-    random_games_undo(1);
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        [] => random_games_undo(1),
+        ["play"] => interactive_game::<Variant>(),
+        ["fog"] => fog_game(None),
+        ["fog", "white"] => fog_game(Some(Color::White)),
+        ["fog", "black"] => fog_game(Some(Color::Black)),
+        ["kriegspiel"] => kriegspiel_game(),
+        ["bughouse"] => bughouse_game([[Seat::Human, Seat::Random], [Seat::Random, Seat::Random]]),
+        ["bughouse", seats] => match read_seats(seats) {
+            Some(seats) => bughouse_game(seats),
+            None => println!("Seats are four of h (human) or r (random): Aw Ab Bw Bb"),
+        },
+        _ => {
+            println!("Usage: shessboat [play | fog [engine color] | kriegspiel | bughouse [seats]]")
+        }
+    }
+}
+
+/// Bughouse seats as four letters, White and Black on board A, then on
+/// board B.
+fn read_seats(s: &str) -> Option<[[Seat; 2]; 2]> {
+    let mut seats = s.chars().map(|c| match c {
+        'h' => Some(Seat::Human),
+        'r' => Some(Seat::Random),
+        _ => None,
+    });
+    let mut next = || seats.next().flatten();
+    let res = [[next()?, next()?], [next()?, next()?]];
+    seats.next().is_none().then_some(res)
 }
 
 fn zobrist_hashing_check(n: usize) {
@@ -54,18 +96,18 @@ fn zobrist_hashing_check(n: usize) {
     for _ in 1..=n {
         engine.setup();
         let mut move_seq = vec![];
-        let mut hash: HashResult = hasher.hash_full(&engine.board);
+        let mut hash: HashResult = engine.rules.hash(&hasher, &engine.board);
 
-        while engine.victory() == None {
+        while engine.victory().is_none() {
             let mv = *engine.moves.choose(&mut rng).unwrap();
             move_seq.push(mv);
-            hash = hasher.delta(&engine.board.metadata, hash, mv);
+            hash = engine.rules.hash_delta(&hasher, &engine.board, hash, mv);
 
             let c = engine.board.metadata.to_move;
 
             engine.apply_move(mv);
 
-            let refhash = hasher.hash_full(&engine.board);
+            let refhash = engine.rules.hash(&hasher, &engine.board);
 
             if refhash != hash {
                 println!("Inconsistency found:\n delta {hash:016X}\n ref-- {refhash:016X}");
@@ -84,9 +126,9 @@ fn zobrist_hashing_check(n: usize) {
                 return;
             }
 
-            if hashes.contains_key(&refhash) {
+            if let Some(q) = hashes.get(&refhash) {
                 let mut e = engine.board.clone();
-                let q = hashes[&refhash].clone();
+                let q = q.clone();
                 e.metadata.tempo = q.metadata.tempo;
                 if e != q {
                     println!("Colission found!");
@@ -155,7 +197,7 @@ fn enumerate_moves_check(mvs: &[ProtoMove], mut depth: usize) {
     fn recurse(board: BitBoard, depth: usize) -> usize {
         let mut moves = Vec::with_capacity(50);
         if depth == 1 {
-            return 1;
+            1
         } else {
             board.generate_moves(&mut moves);
             let mut sum = 0;
@@ -164,7 +206,7 @@ fn enumerate_moves_check(mvs: &[ProtoMove], mut depth: usize) {
                 b.apply(mv);
                 sum += recurse(b, depth - 1);
             }
-            return sum;
+            sum
         }
     }
 }
@@ -182,17 +224,20 @@ fn random_games_undo(n: usize) {
         let mut move_log = Vec::new();
         board.generate_moves(&mut moves);
 
-        while let None = GameEnd::determine(&board, &moves, 0, &changes, &three) {
+        while StandardChess
+            .game_end(&board, &moves, 0, &changes, &three)
+            .is_none()
+        {
             let mv = *moves.choose(&mut rng).unwrap();
-            board.apply(mv);
+            let undo = board.apply(mv);
             moves.clear();
             board.generate_moves(&mut moves);
-            move_log.push((mv, board.clone()));
+            move_log.push((mv, undo, board.clone()));
         }
 
         move_log.reverse();
 
-        for (mv, refboard) in move_log {
+        for (mv, undo, refboard) in move_log {
             if board != refboard {
                 println!("Inconsistency found");
                 println!("ref:");
@@ -207,7 +252,7 @@ fn random_games_undo(n: usize) {
                 println!("{:?}", board.metadata);
                 break;
             }
-            board.undo(mv);
+            board.undo(mv, &undo);
         }
     }
 
@@ -227,8 +272,8 @@ fn random_games_move_enumeration_benchmark(n: usize) {
     for _ in 1..=n {
         engine.setup();
 
-        while engine.victory() == None {
-            let mv = engine.moves.choose(&mut rng).unwrap().clone();
+        while engine.victory().is_none() {
+            let mv = *engine.moves.choose(&mut rng).unwrap();
             let now = Instant::now();
             engine.apply_move(mv);
             let delta = now.elapsed();
@@ -268,11 +313,13 @@ fn random_games_move_enumeration_benchmark(n: usize) {
     println!()
 }
 
-fn interactive_game() {
+fn interactive_game<R: Rules + Default + Clone>() {
     let mut rng = ThreadRng::default();
-    let mut interactor = ShessInteractor::new();
+    let mut interactor = ShessInteractor::with_rules(R::default());
     interactor.setup();
-    let mut move_log = Vec::<(Algebraic, &'static str)>::new();
+    let mut record = GameRecord::new(interactor.rules.clone(), interactor.board.clone());
+    let mut engine = Shessboat::new(interactor.rules.clone());
+    let mut clock: Option<Clock> = None;
     let mut highlight = Mask::nil();
 
     'redraw: loop {
@@ -283,6 +330,8 @@ fn interactive_game() {
             let mut s = String::new();
             if let Some(vic) = interactor.victory() {
                 print!("{}> ", vic.to_str());
+            } else if let Some(clock) = &clock {
+                print!("{:?} [{}]> ", interactor.to_move(), clock);
             } else {
                 print!("{:?}> ", interactor.to_move());
             }
@@ -292,7 +341,7 @@ fn interactive_game() {
                 .trim()
                 .split(|c: char| c.is_whitespace())
                 .collect::<Vec<_>>();
-            if command.len() < 1 {
+            if command.is_empty() {
                 continue 'command_loop;
             }
             match command[0] {
@@ -303,15 +352,61 @@ fn interactive_game() {
                     continue 'redraw;
                 }
                 "new" => {
+                    // new [variant] [pawn|knight|rook|queen|<squares>]
+                    let mut args = &command[1..];
+                    let rules = match args.first().and_then(|v| R::read(v)) {
+                        Some((rules, "")) => {
+                            args = &args[1..];
+                            rules
+                        }
+                        _ => R::default(),
+                    };
+                    let odds = match args {
+                        [] => None,
+                        [o] => match Odds::read(o) {
+                            Some((o, "")) => Some(o),
+                            _ => {
+                                println!("Unknown variant or odds");
+                                continue 'command_loop;
+                            }
+                        },
+                        _ => {
+                            println!("Format: new [variant] [odds]");
+                            continue 'command_loop;
+                        }
+                    };
+
                     highlight = Mask::nil();
+                    engine.rules = rules.clone();
+                    engine.table.clear();
+                    engine.ordering.clear();
+                    interactor.rules = rules;
                     interactor.setup();
-                    move_log.clear();
+                    if let Some(odds) = odds
+                        && let Err(e) = interactor.give_odds(&odds)
+                    {
+                        println!("Error: {}", e);
+                        continue 'command_loop;
+                    }
+                    record.moves.clear();
+                    if let Some(clock) = &mut clock {
+                        clock.reset();
+                    }
                     continue 'redraw;
                 }
+                "pgn" => {
+                    println!("{}", record.write_pgn(interactor.victory()));
+                    continue 'command_loop;
+                }
                 "reset" => {
                     highlight = Mask::nil();
                     interactor.reset();
-                    move_log.clear();
+                    engine.table.clear();
+                    engine.ordering.clear();
+                    record.moves.clear();
+                    if let Some(clock) = &mut clock {
+                        clock.reset();
+                    }
                     continue 'redraw;
                 }
                 "threats" => {
@@ -331,7 +426,7 @@ fn interactive_game() {
                 "i" => {
                     if let (Some(p), Some(sq)) = (command.get(1), command.get(2)) {
                         if let (Some((p, "")), Some((sq, ""))) =
-                            (ColorPiece::read(*p), Square::read(*sq))
+                            (ColorPiece::read(p), Square::read(sq))
                         {
                             interactor.place(Some(p), sq);
                             continue 'redraw;
@@ -368,7 +463,7 @@ fn interactive_game() {
                 }
                 "ls" => {
                     let legal_moves = interactor.printable_moves();
-                    if legal_moves.len() == 0 {
+                    if legal_moves.is_empty() {
                         println!("No legal moves");
                     }
                     for mvs in legal_moves.chunks(8) {
@@ -389,32 +484,127 @@ fn interactive_game() {
                         let Some(mv) = interactor.moves.choose(&mut rng) else {
                             continue 'redraw;
                         };
-                        let not = Algebraic::new(&mv, &interactor.moves);
-                        match interactor.normal_move(not) {
+                        let not = interactor.rules.notation(mv, &interactor.moves);
+                        if record.moves.is_empty() {
+                            record =
+                                GameRecord::new(interactor.rules.clone(), interactor.board.clone());
+                        }
+                        match interactor.play(not) {
                             Ok(ns) => {
-                                move_log.push(ns.0);
+                                record.moves.push(ns.0);
                             }
                             Err(e) => {}
                         }
                     }
                     continue 'redraw;
                 }
+                "go" => {
+                    // go [depth | wtime <ms> btime <ms> winc <ms> binc <ms> movestogo <n>
+                    //     movetime <ms> nodes <n> depth <n>]
+                    let limits = if let Some(depth) = command.get(1).and_then(|n| n.parse().ok()) {
+                        Limits::depth(depth)
+                    } else if command.len() > 1 {
+                        match Limits::read(&command[1..].join(" ")) {
+                            Some((limits, "")) => limits,
+                            _ => {
+                                println!("Format: go [depth | <limit> <value>...]");
+                                continue 'command_loop;
+                            }
+                        }
+                    } else if let Some(clock) = &clock {
+                        clock.limits()
+                    } else {
+                        Limits::depth(4)
+                    };
+                    let color = interactor.to_move();
+                    let res = engine.go(
+                        &interactor.board,
+                        &limits,
+                        &interactor.last_change,
+                        &interactor.repetitions,
+                    );
+                    let Some(mv) = res.best else {
+                        println!("No legal moves");
+                        continue 'command_loop;
+                    };
+                    for it in &engine.iterations {
+                        println!(
+                            "depth {} score {} pv {}",
+                            it.depth,
+                            write_score(it.score),
+                            write_pv(&interactor.rules, &interactor.board, &it.pv)
+                        );
+                    }
+                    println!("hashfull {}", engine.table.hashfull());
+                    println!("{}", engine.ordering.statistics);
+
+                    let not = interactor.rules.notation(&mv, &interactor.moves);
+                    if record.moves.is_empty() {
+                        record =
+                            GameRecord::new(interactor.rules.clone(), interactor.board.clone());
+                    }
+                    if let Ok(ns) = interactor.play(not) {
+                        record.moves.push(ns.0);
+                        highlight = ns.1.from_to.as_mask();
+                        end_turn(&mut clock, color);
+                    }
+                    continue 'command_loop;
+                }
+                "clock" => {
+                    // clock <minutes> [increment in seconds] | clock off
+                    if let Some(&"off") = command.get(1) {
+                        clock = None;
+                        continue 'command_loop;
+                    }
+                    let Some(minutes) = command.get(1).and_then(|n| n.parse::<f64>().ok()) else {
+                        if let Some(clock) = &clock {
+                            println!("{}", clock);
+                        } else {
+                            println!("Format: clock <minutes> [increment] | clock off");
+                        }
+                        continue 'command_loop;
+                    };
+                    let increment = command.get(2).and_then(|n| n.parse().ok()).unwrap_or(0.0);
+                    clock = Some(Clock::new(
+                        Duration::from_secs_f64(minutes * 60.0),
+                        Duration::from_secs_f64(increment),
+                    ));
+                    continue 'command_loop;
+                }
+                "hash" => {
+                    // hash [size in MB]
+                    if let Some(mb) = command.get(1).and_then(|n| n.parse().ok()) {
+                        engine.table.resize(mb);
+                    } else {
+                        println!("hashfull {}", engine.table.hashfull());
+                    }
+                    continue 'command_loop;
+                }
+                "set" => {
+                    // set <null|lmr|rfp|futility|lmp> <on|off>
+                    let on = match command.get(2) {
+                        Some(&"on") => true,
+                        Some(&"off") => false,
+                        _ => {
+                            println!("Format: set <technique> <on|off>");
+                            continue 'command_loop;
+                        }
+                    };
+                    if let Err(e) = engine.selectivity.set(command[1], on) {
+                        println!("Error: {}", e);
+                    }
+                    continue 'command_loop;
+                }
                 "log" => {
                     if let Some(&"clear") = command.get(1) {
-                        move_log.clear();
+                        record.moves.clear();
                     } else {
-                        for (n, mv) in move_log.chunks(2).enumerate() {
-                            print!("{}. ", n + 1);
-                            for m in mv {
-                                print!("{}{} ", m.0, m.1);
-                            }
-                            println!();
-                        }
+                        println!("{}", write_move_list(&record.moves));
                     }
                     continue 'command_loop;
                 }
                 "clear" => {
-                    move_log.clear();
+                    record.moves.clear();
                     continue 'command_loop;
                 }
                 "meta" => {
@@ -435,10 +625,16 @@ fn interactive_game() {
                 }
                 s => {
                     if let Some((n, "")) = Algebraic::read(s) {
-                        match interactor.normal_move(n) {
+                        if record.moves.is_empty() {
+                            record =
+                                GameRecord::new(interactor.rules.clone(), interactor.board.clone());
+                        }
+                        let color = interactor.to_move();
+                        match interactor.play(n) {
                             Ok(ns) => {
-                                move_log.push(ns.0);
+                                record.moves.push(ns.0);
                                 highlight = ns.1.from_to.as_mask();
+                                end_turn(&mut clock, color);
                                 continue 'redraw;
                             }
                             Err(e) => {
@@ -456,7 +652,177 @@ fn interactive_game() {
     }
 }
 
+/// Charges `color` for the move it just made, announcing a flag fall.
+fn end_turn(clock: &mut Option<Clock>, color: Color) {
+    if let Some(clock) = clock {
+        clock.end_turn(color);
+        if clock.flagged(color) {
+            println!("{:?} ran out of time", color);
+        }
+    }
+}
+
+fn bughouse_game(seats: [[Seat; 2]; 2]) {
+    let mut rng = ThreadRng::default();
+    let mut bughouse = Bughouse::new(seats);
+
+    loop {
+        print!("\x1B[2J\x1B[1;1H");
+        for id in [BoardId::A, BoardId::B] {
+            let interactor = bughouse.board(id);
+            println!("  Board {:?}", id);
+            print_chessboard(&interactor.as_boardmap(), Mask::nil());
+            println!("{}", interactor.printable_metadata());
+        }
+        stdout().flush();
+
+        if let Some(vic) = bughouse.victory() {
+            println!("{}", bughouse.write_bpgn());
+            println!("{}", vic.to_str());
+            return;
+        }
+
+        let id = bughouse.next;
+        if bughouse.seat(id) == Seat::Random {
+            if let Err(e) = bughouse.random_move(id, &mut rng) {
+                println!("Error: {}", e);
+                return;
+            }
+            sleep(Duration::from_millis(250));
+            continue;
+        }
+
+        loop {
+            print!("{}> ", id.letter(bughouse.board(id).to_move()));
+            stdout().flush();
+            let mut s = String::new();
+            stdin().read_line(&mut s);
+
+            match s.trim() {
+                "exit" => return,
+                "bpgn" => println!("{}", bughouse.write_bpgn()),
+                s => {
+                    if let Some((n, "")) = Algebraic::read(s) {
+                        match bughouse.play(id, n) {
+                            Ok(_) => break,
+                            Err(e) => println!("Error: {}", e),
+                        }
+                    } else {
+                        println!("Unrecognized command");
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn fog_game(engine: Option<Color>) {
+    let mut rng = ThreadRng::default();
+    let mut interactor = ShessInteractor::new();
+    interactor.setup_variant(Variant::FogOfWar);
+
+    loop {
+        if let Some(vic) = interactor.victory() {
+            print!("\x1B[2J\x1B[1;1H");
+            print_chessboard(&interactor.as_boardmap(), Mask::nil());
+            println!("{}", vic.to_str());
+            return;
+        }
+
+        let color = interactor.to_move();
+        if Some(color) == engine {
+            let Some(mv) = fog_move(&interactor.board, &mut rng) else {
+                return;
+            };
+            interactor.apply_move(mv);
+            continue;
+        }
+
+        print!("\x1B[2J\x1B[1;1H");
+        print!("{color:?} to move, press enter when ready");
+        stdout().flush();
+        stdin().read_line(&mut String::new());
+
+        let visible = interactor.board.visible(color);
+        print!("\x1B[2J\x1B[1;1H");
+        print_fogged_chessboard(&interactor.as_boardmap(), Mask::nil(), visible);
+
+        loop {
+            print!("{color:?}> ");
+            stdout().flush();
+            let mut s = String::new();
+            stdin().read_line(&mut s);
+
+            match s.trim() {
+                "exit" => return,
+                s => {
+                    if let Some((n, "")) = Algebraic::read(s) {
+                        match interactor.normal_move(n) {
+                            Ok(_) => break,
+                            Err(e) => println!("Error: {}", e),
+                        }
+                    } else {
+                        println!("Unrecognized command");
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn kriegspiel_game() {
+    let mut game = Kriegspiel::new(Variant::Standard);
+    let mut heard = Vec::<String>::new();
+
+    loop {
+        if let Some(vic) = game.interactor.victory() {
+            print!("\x1B[2J\x1B[1;1H");
+            print_chessboard(&game.interactor.as_boardmap(), Mask::nil());
+            println!("{}\n{}", vic.to_str(), game.write_referee_log());
+            return;
+        }
+
+        let color = game.interactor.to_move();
+        print!("\x1B[2J\x1B[1;1H");
+        print!("{color:?} to move, press enter when ready");
+        stdout().flush();
+        stdin().read_line(&mut String::new());
+
+        'attempt: loop {
+            print!("\x1B[2J\x1B[1;1H");
+            print_chessboard(&game.own_pieces(color), Mask::nil());
+            for h in &heard {
+                println!("Referee: {h}");
+            }
+            print!("{color:?}> ");
+            stdout().flush();
+            let mut s = String::new();
+            stdin().read_line(&mut s);
+
+            match s.trim() {
+                "exit" => return,
+                s => {
+                    let Some((n, "")) = Algebraic::read(s) else {
+                        heard = vec!["unrecognized".to_string()];
+                        continue 'attempt;
+                    };
+                    let announcements = game.referee(n);
+                    heard = announcements.iter().map(|a| a.to_string()).collect();
+                    if announcements != [Announcement::Illegal] {
+                        break 'attempt;
+                    }
+                }
+            }
+        }
+    }
+}
+
 fn print_chessboard(pieces: &BoardMap<Option<ColorPiece>>, highlights: Mask) {
+    print_fogged_chessboard(pieces, highlights, Mask::full());
+}
+
+/// Prints only the `visible` squares, drawing the rest as fog.
+fn print_fogged_chessboard(pieces: &BoardMap<Option<ColorPiece>>, highlights: Mask, visible: Mask) {
     let chessboard = [
         Rank::_8,
         Rank::_7,
@@ -470,7 +836,11 @@ fn print_chessboard(pieces: &BoardMap<Option<ColorPiece>>, highlights: Mask) {
     for rank in chessboard {
         print!("  ");
         for sq in rank.as_mask().iter() {
-            let piece = pieces.at(sq);
+            let piece = if visible.contains(sq) {
+                pieces.at(sq)
+            } else {
+                None
+            };
             let mut fg_color = match piece {
                 Some(p) if p.color() == Color::White => colored::Color::TrueColor {
                     r: 0xFF,
@@ -484,7 +854,13 @@ fn print_chessboard(pieces: &BoardMap<Option<ColorPiece>>, highlights: Mask) {
                 },
             };
 
-            let bg_color = if highlights.contains(sq) {
+            let bg_color = if !visible.contains(sq) {
+                colored::Color::TrueColor {
+                    r: 0x2F,
+                    g: 0x2F,
+                    b: 0x3F,
+                }
+            } else if highlights.contains(sq) {
                 colored::Color::TrueColor {
                     r: 0xAF,
                     g: 0x7F,
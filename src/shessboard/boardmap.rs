@@ -31,7 +31,7 @@ impl<T: Sized + Copy> BoardMap<T> {
         self
     }
 
-    pub const fn iter(&self) -> BoardMapIter<T>
+    pub const fn iter(&self) -> BoardMapIter<'_, T>
     where
         T: Sized,
     {
@@ -100,10 +100,10 @@ impl BoardMap<Option<ColorPiece>> {
         let mut res = Mask::nil();
         let mut it = self.iter();
         while let Some((sq, x)) = it.next() {
-            if let Some(x) = x {
-                if c as u8 == x as u8 {
-                    res = res.set(sq);
-                }
+            if let Some(x) = x
+                && c as u8 == x as u8
+            {
+                res = res.set(sq);
             }
         }
         res
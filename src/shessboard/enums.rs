@@ -2,9 +2,10 @@ use crate::shessboard::{
     BitBoard,
     forced_draws::{LastChange, ThreefoldRule},
     moves::Move,
-    pieces::{Millipawns, P},
+    pieces::{Millipawns, P, kings::Kings},
+    rules::Rules,
     squares::Square,
-    zobrist::HashResult,
+    zobrist::{BitBoardHasher, HashResult},
 };
 
 use super::masks::Mask;
@@ -83,6 +84,7 @@ pub enum Rank {
 }
 
 impl Rank {
+    #[allow(clippy::just_underscores_and_digits)]
     pub const fn as_mask(&self) -> Mask {
         use Rank::*;
         Mask::visboard(match self {
@@ -97,6 +99,7 @@ impl Rank {
         })
     }
 
+    #[allow(clippy::self_named_constructors)]
     pub const fn rank(e: i8) -> Option<Self> {
         use Rank::*;
         match e {
@@ -178,6 +181,7 @@ impl File {
         })
     }
 
+    #[allow(clippy::self_named_constructors)]
     pub const fn file(e: i8) -> Option<Self> {
         use File::*;
         match e {
@@ -246,6 +250,17 @@ pub enum Piece {
 }
 
 impl Piece {
+    pub const fn materiel(&self) -> Millipawns {
+        match self {
+            Piece::Pawn => 1_000,
+            Piece::Knight => 3_250,
+            Piece::Bishop => 3_333,
+            Piece::Rook => 5_000,
+            Piece::Queen => 9_000,
+            Piece::King => 0,
+        }
+    }
+
     pub const fn white_letter(&self) -> char {
         match self {
             Piece::Pawn => 'P',
@@ -464,6 +479,74 @@ impl ColorPiece {
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[repr(u8)]
+pub enum Variant {
+    #[default]
+    Standard = 1,
+    KingOfTheHill,
+    ThreeCheck,
+    Antichess,
+    Atomic,
+    Crazyhouse,
+    Bughouse,
+    Horde,
+    RacingKings,
+    FogOfWar,
+    Knightmate,
+    Extinction,
+    Marseillais,
+}
+
+impl Variant {
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Variant::Standard => "standard",
+            Variant::KingOfTheHill => "koth",
+            Variant::ThreeCheck => "3check",
+            Variant::Antichess => "antichess",
+            Variant::Atomic => "atomic",
+            Variant::Crazyhouse => "crazyhouse",
+            Variant::Bughouse => "bughouse",
+            Variant::Horde => "horde",
+            Variant::RacingKings => "racingkings",
+            Variant::FogOfWar => "fog",
+            Variant::Knightmate => "knightmate",
+            Variant::Extinction => "extinction",
+            Variant::Marseillais => "marseillais",
+        }
+    }
+
+    /// The piece whose capture loses the game, and which may not be left in
+    /// check.
+    pub const fn has_drops(&self) -> bool {
+        matches!(self, Variant::Crazyhouse | Variant::Bughouse)
+    }
+
+    pub fn read(s: &str) -> Option<(Self, &str)> {
+        for v in [
+            Variant::Standard,
+            Variant::KingOfTheHill,
+            Variant::ThreeCheck,
+            Variant::Antichess,
+            Variant::Atomic,
+            Variant::Crazyhouse,
+            Variant::Bughouse,
+            Variant::Horde,
+            Variant::RacingKings,
+            Variant::FogOfWar,
+            Variant::Knightmate,
+            Variant::Extinction,
+            Variant::Marseillais,
+        ] {
+            if let Some(s) = s.strip_prefix(v.name()) {
+                return Some((v, s));
+            }
+        }
+        None
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum GameEnd {
@@ -503,7 +586,20 @@ impl GameEnd {
         }
     }
 
-    pub fn determine<'a>(
+    pub const fn pgn_str(&self) -> &'static str {
+        match self {
+            Self::WhiteWins => "1-0",
+            Self::BlackWins => "0-1",
+            Self::Draw => "1/2-1/2",
+        }
+    }
+
+    /// How a game ended by the ends of plain chess, given the legal moves in
+    /// `board`, with checks and material as `rules` count them. Variants
+    /// check their own ends first.
+    #[allow(clippy::if_same_then_else)]
+    pub fn determine<'a, R: Rules + ?Sized>(
+        rules: &R,
         board: &BitBoard,
         moves: &[Move],
         hash: HashResult,
@@ -512,15 +608,15 @@ impl GameEnd {
     ) -> Option<Self> {
         if board.metadata.tempo - change.tempo() >= 150 {
             Some(Self::Draw)
-        } else if moves.len() == 0 {
-            if board.is_in_check(board.metadata.to_move) {
+        } else if moves.is_empty() {
+            if rules.is_in_check(board, board.metadata.to_move) {
                 Some(Self::from_color(board.metadata.to_move.other()))
             } else {
                 Some(Self::Draw)
             }
-        } else if !board.sufficient_checkmating_materiel() {
+        } else if !rules.sufficient_material(board) {
             Some(Self::Draw)
-        } else if three.count(hash) >= 3 {
+        } else if three.count(hash & BitBoardHasher::HASH_BITS) >= 3 {
             Some(Self::Draw)
         } else {
             None
@@ -0,0 +1,84 @@
+use crate::shessboard::{
+    castling::{CastlingDetails, CastlingRights},
+    enums::{Color, Piece},
+    half::HalfBitBoard,
+    masks::Mask,
+    pieces::kings::Kings,
+    squares::Square,
+};
+
+/// Pieces removed by an atomic capture, kept so the capture can be undone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explosion {
+    pub white: HalfBitBoard,
+    pub black: HalfBitBoard,
+}
+
+impl Explosion {
+    pub const fn blast(at: Square) -> Mask {
+        Kings::moves_from(at)
+    }
+
+    /// Removes the capturing piece standing on `at` along with every
+    /// non-pawn piece next to it.
+    pub fn detonate(white: &mut HalfBitBoard, black: &mut HalfBitBoard, at: Square) -> Self {
+        let res = Self {
+            white: Self::fallout(white, at),
+            black: Self::fallout(black, at),
+        };
+        white.toggle(&res.white);
+        black.toggle(&res.black);
+        res
+    }
+
+    fn fallout(half: &HalfBitBoard, at: Square) -> HalfBitBoard {
+        let blast = Self::blast(at).set(at);
+        let mut res = HalfBitBoard::nil();
+
+        for piece in [
+            Piece::Knight,
+            Piece::Bishop,
+            Piece::Rook,
+            Piece::Queen,
+            Piece::King,
+        ] {
+            *res.piece_mask_mut(piece) = half.piece_mask(piece) & blast;
+        }
+        *res.piece_mask_mut(Piece::Pawn) = half.piece_mask(Piece::Pawn) & at.as_mask();
+
+        res
+    }
+
+    pub fn restore(&self, white: &mut HalfBitBoard, black: &mut HalfBitBoard) {
+        white.toggle(&self.white);
+        black.toggle(&self.black);
+    }
+
+    pub fn color(&self, color: Color) -> &HalfBitBoard {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    /// Castling rights left untouched by the explosion, in the same sense as
+    /// [`crate::shessboard::moves::Move::castling_rights`].
+    pub fn castling_rights(&self, details: CastlingDetails, color: Color) -> CastlingRights {
+        let fallout = self.color(color);
+        let rank = color.starting_rank();
+        let king = fallout.kings.as_mask().any();
+
+        CastlingRights {
+            ooo: !king
+                && !fallout
+                    .rooks
+                    .as_mask()
+                    .contains(Square::at(details.ooo.rook_move.from, rank)),
+            oo: !king
+                && !fallout
+                    .rooks
+                    .as_mask()
+                    .contains(Square::at(details.oo.rook_move.from, rank)),
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use crate::shessboard::{
+    BitBoard,
+    enums::{Color, Rank, Variant},
+    masks::Mask,
+    rules::Rules,
+    variants::FogOfWar,
+};
+
+impl BitBoard {
+    /// Squares seen by `color` in fog of war: its own pieces, every square
+    /// it could move to or capture on, and every square it attacks.
+    pub fn visible(&self, color: Color) -> Mask {
+        let mut board = self.clone();
+        if board.metadata.to_move != color {
+            board.metadata.to_move = color;
+            board.metadata.en_passant = None;
+        }
+
+        let mut moves = Vec::with_capacity(50);
+        FogOfWar.generate_moves(&board, &mut moves);
+
+        let own = self.color(color);
+        let mut res = own.as_mask() | own.threats(color, self.color(color.other()).as_mask(), None);
+        for mv in moves {
+            res = res.set(mv.from_to.to);
+            if let Some((sq, _)) = mv.capture {
+                res = res.set(sq);
+            }
+        }
+        res
+    }
+
+    /// The position as `color` sees it, with hidden enemy pieces removed.
+    pub fn fogged(&self, color: Color) -> Self {
+        let visible = self.visible(color);
+        let mut res = self.clone();
+        let (_, passive) = res.color_mut(color);
+
+        for sq in passive.as_mask() & !visible {
+            passive.delete(sq);
+        }
+
+        res
+    }
+}
+
+#[test]
+fn starting_visibility() {
+    let board = FogOfWar.start();
+    let white = Rank::_1.as_mask() | Rank::_2.as_mask() | Rank::_3.as_mask() | Rank::_4.as_mask();
+
+    assert_eq!(board.visible(Color::White), white);
+    assert_eq!(board.visible(Color::Black), white.mirror());
+    assert_eq!(board.fogged(Color::White).as_mask(), board.white.as_mask());
+}
+
+#[test]
+fn fog_king_capture() {
+    use crate::shessboard::{
+        enums::{ColorPiece, GameEnd},
+        forced_draws::{LastChange, ThreefoldRule},
+        squares::Square,
+    };
+
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::FogOfWar;
+    board.set_piece(Some(ColorPiece::WhiteKing), Square::e1);
+    board.set_piece(Some(ColorPiece::WhiteRook), Square::h2);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::e8);
+    board.set_piece(Some(ColorPiece::BlackRook), Square::h1);
+
+    let mut moves = vec![];
+    FogOfWar.generate_moves(&board, &mut moves);
+    assert!(moves.iter().any(|m| m.from_to == Square::e1.to(Square::f1)));
+    assert!(!FogOfWar.gives_check(&board, moves[0]));
+
+    let fogged = board.fogged(Color::White);
+    assert_eq!(fogged.black.as_mask(), Square::h1.as_mask());
+
+    let mv = *moves
+        .iter()
+        .find(|m| m.from_to == Square::h2.to(Square::h8))
+        .unwrap();
+    FogOfWar.apply(&mut board, mv);
+    moves.clear();
+    FogOfWar.generate_moves(&board, &mut moves);
+    let mv = *moves
+        .iter()
+        .find(|m| m.from_to == Square::h1.to(Square::e1))
+        .unwrap();
+    FogOfWar.apply(&mut board, mv);
+    moves.clear();
+    FogOfWar.generate_moves(&board, &mut moves);
+
+    assert_eq!(
+        FogOfWar.game_end(
+            &board,
+            &moves,
+            0,
+            &LastChange::start(),
+            &ThreefoldRule::empty()
+        ),
+        Some(GameEnd::BlackWins)
+    );
+}
@@ -56,6 +56,7 @@ impl ThreefoldRule<'static> {
         Self::Static(HashMap::from_iter([(hash & BitBoardHasher::HASH_BITS, 1)]))
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_iter<I>(it: I) -> Self
     where
         I: IntoIterator<Item = HashResult>,
@@ -81,7 +82,7 @@ impl<'a> ThreefoldRule<'a> {
             match tfr {
                 ThreefoldRule::Static(hash_map) => hash_map.clone(),
                 ThreefoldRule::Speculative(hash, threefold_rule) => {
-                    let mut res = recurse(*threefold_rule);
+                    let mut res = recurse(threefold_rule);
                     *res.entry(*hash).or_insert(0) += 1;
                     res
                 }
@@ -66,6 +66,37 @@ impl HalfBitBoard {
             .overlay(self.pawns.as_mask())
     }
 
+    pub const fn mirror(&self) -> Self {
+        Self {
+            kings: Kings::new(self.kings.as_mask().mirror()),
+            queens: Queens::new(self.queens.as_mask().mirror()),
+            rooks: Rooks::new(self.rooks.as_mask().mirror()),
+            bishops: Bishops::new(self.bishops.as_mask().mirror()),
+            knights: Knights::new(self.knights.as_mask().mirror()),
+            pawns: Pawns::new(self.pawns.as_mask().mirror()),
+        }
+    }
+
+    pub const fn reflect(&self) -> Self {
+        Self {
+            kings: Kings::new(self.kings.as_mask().reflect()),
+            queens: Queens::new(self.queens.as_mask().reflect()),
+            rooks: Rooks::new(self.rooks.as_mask().reflect()),
+            bishops: Bishops::new(self.bishops.as_mask().reflect()),
+            knights: Knights::new(self.knights.as_mask().reflect()),
+            pawns: Pawns::new(self.pawns.as_mask().reflect()),
+        }
+    }
+
+    pub fn toggle(&mut self, other: &HalfBitBoard) {
+        *self.kings.mut_mask() ^= other.kings.as_mask();
+        *self.queens.mut_mask() ^= other.queens.as_mask();
+        *self.rooks.mut_mask() ^= other.rooks.as_mask();
+        *self.bishops.mut_mask() ^= other.bishops.as_mask();
+        *self.knights.mut_mask() ^= other.knights.as_mask();
+        *self.pawns.mut_mask() ^= other.pawns.as_mask();
+    }
+
     pub const fn piece_mask(&self, piece: Piece) -> Mask {
         match piece {
             Piece::Pawn => self.pawns.as_mask(),
@@ -184,6 +215,38 @@ impl HalfBitBoard {
             || (knights == 1 && (light_bishops + dark_bishops) == 1)
     }
 
+    /// Antichess is only drawn by material when both sides have nothing but
+    /// bishops, and those bishops can never meet on the same shade.
+    pub fn has_sufficient_antichess_materiel(&self, other: &HalfBitBoard) -> bool {
+        let only_bishops =
+            |h: &HalfBitBoard| h.as_mask().any() && h.as_mask() == h.bishops.as_mask();
+
+        if !only_bishops(self) || !only_bishops(other) {
+            return true;
+        }
+
+        let shades = |h: &HalfBitBoard| {
+            (
+                h.bishops.as_mask().overlap(Shade::Light.as_mask()).any(),
+                h.bishops.as_mask().overlap(Shade::Dark.as_mask()).any(),
+            )
+        };
+
+        !matches!(
+            (shades(self), shades(other)),
+            ((true, false), (false, true)) | ((false, true), (true, false))
+        )
+    }
+
+    /// The first piece type with no pieces left, which loses in extinction
+    /// chess.
+    pub fn extinct(&self) -> Option<Piece> {
+        use Piece::*;
+        [King, Queen, Rook, Bishop, Knight, Pawn]
+            .into_iter()
+            .find(|&p| !self.piece_mask(p).any())
+    }
+
     pub fn materiel(&self) -> Millipawns {
         self.pawns.materiel()
             + self.knights.materiel()
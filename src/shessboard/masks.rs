@@ -41,6 +41,10 @@ impl Mask {
         Mask::new(self.as_u64().reverse_bits())
     }
 
+    pub const fn reflect(&self) -> Self {
+        self.rotate().mirror()
+    }
+
     pub const fn overlap(&self, other: Mask) -> Mask {
         Mask::new(self.as_u64() & other.as_u64())
     }
@@ -235,6 +239,16 @@ impl Iterator for SquareIter {
     }
 }
 
+#[test]
+fn mirror_rotate_reflect() {
+    let m = Mask::nil().set(Square::b1).set(Square::h3);
+
+    assert_eq!(m.mirror(), Mask::nil().set(Square::b8).set(Square::h6));
+    assert_eq!(m.rotate(), Mask::nil().set(Square::g8).set(Square::a6));
+    assert_eq!(m.reflect(), Mask::nil().set(Square::g1).set(Square::a3));
+    assert_eq!(m.reflect().reflect(), m);
+}
+
 #[test]
 fn test_mask_first_and_iteration() {
     assert_eq!(Mask::new(0x1).first(), Square::new(0));
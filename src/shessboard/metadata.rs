@@ -2,18 +2,74 @@ use std::str;
 
 use crate::shessboard::{
     castling::{CastlingDetails, CastlingRights},
-    enums::{Color, Piece},
+    enums::{Color, Piece, Variant},
+    masks::Mask,
+    moves::Move,
     pieces::pawns::EnPassant,
+    pockets::Pocket,
 };
 
+/// Where a marseillais turn stands: each side moves twice, unless its first
+/// move gives check.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct SubTurn {
+    /// The side to move has already made the first move of its turn.
+    pub second: bool,
+    /// The double step from the first move of the previous turn, which can be
+    /// taken en passant alongside `Metadata::en_passant`.
+    pub first_en_passant: Option<EnPassant>,
+}
+
+impl SubTurn {
+    /// The sub-turn after `mv`, given whether it gives check and where the
+    /// mover's pawns stand once it is played. The mover keeps the turn for a
+    /// second move unless the first one gave check or this was the second.
+    pub fn next(self, mv: Move, check: bool, pawns: Mask) -> Self {
+        if !self.second && !check {
+            SubTurn {
+                second: true,
+                first_en_passant: None,
+            }
+        } else {
+            // the first double step stays capturable while its pawn stands
+            let first_en_passant = mv
+                .prev_epc
+                .filter(|_| self.second)
+                .map(|to| EnPassant { to })
+                .filter(|ep| pawns.contains(ep.capture()));
+            SubTurn {
+                second: false,
+                first_en_passant,
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Metadata {
+    pub variant: Variant,
     pub to_move: Color,
+    /// Moves made, counting both moves of a marseillais turn.
     pub tempo: u16,
+    /// Times the side to move has changed, which numbers the turns.
+    pub half_turns: u16,
     pub white_castling: CastlingRights,
     pub black_castling: CastlingRights,
     pub castling_details: CastlingDetails,
     pub en_passant: Option<EnPassant>,
+    pub white_checks: u8,
+    pub black_checks: u8,
+    pub white_pocket: Pocket,
+    pub black_pocket: Pocket,
+    /// Pieces that started out as pawns, which return to the pocket as pawns when captured.
+    pub promoted: Mask,
+    pub sub_turn: SubTurn,
+}
+
+impl Default for Metadata {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Metadata {
@@ -34,18 +90,101 @@ impl Metadata {
         }
     }
 
+    pub fn checks(&self, color: Color) -> u8 {
+        match color {
+            Color::White => self.white_checks,
+            Color::Black => self.black_checks,
+        }
+    }
+
+    pub fn checks_mut(&mut self, color: Color) -> &mut u8 {
+        match color {
+            Color::White => &mut self.white_checks,
+            Color::Black => &mut self.black_checks,
+        }
+    }
+
+    pub fn pocket(&self, color: Color) -> &Pocket {
+        match color {
+            Color::White => &self.white_pocket,
+            Color::Black => &self.black_pocket,
+        }
+    }
+
+    pub fn pocket_mut(&mut self, color: Color) -> &mut Pocket {
+        match color {
+            Color::White => &mut self.white_pocket,
+            Color::Black => &mut self.black_pocket,
+        }
+    }
+
+    pub fn color_flip(&self) -> Self {
+        Self {
+            to_move: self.to_move.other(),
+            white_checks: self.black_checks,
+            black_checks: self.white_checks,
+            white_pocket: self.black_pocket,
+            black_pocket: self.white_pocket,
+            promoted: self.promoted.mirror(),
+            white_castling: self.black_castling,
+            black_castling: self.white_castling,
+            en_passant: self.en_passant.map(|ep| EnPassant { to: ep.to.mirror() }),
+            sub_turn: SubTurn {
+                first_en_passant: self
+                    .sub_turn
+                    .first_en_passant
+                    .map(|ep| EnPassant { to: ep.to.mirror() }),
+                ..self.sub_turn
+            },
+            ..self.clone()
+        }
+    }
+
+    pub fn reflect(&self) -> Option<Self> {
+        let no_castling = CastlingRights {
+            ooo: false,
+            oo: false,
+        };
+
+        if self.white_castling != no_castling || self.black_castling != no_castling {
+            return None;
+        }
+
+        Some(Self {
+            promoted: self.promoted.reflect(),
+            en_passant: self.en_passant.map(|ep| EnPassant {
+                to: ep.to.reflect(),
+            }),
+            sub_turn: SubTurn {
+                first_en_passant: self.sub_turn.first_en_passant.map(|ep| EnPassant {
+                    to: ep.to.reflect(),
+                }),
+                ..self.sub_turn
+            },
+            ..self.clone()
+        })
+    }
+
     pub fn turn(&self) -> usize {
-        (self.tempo / 2 + 1) as usize
+        (self.half_turns / 2 + 1) as usize
     }
 
     pub fn new() -> Self {
         Self {
+            variant: Variant::Standard,
             to_move: Color::White,
-            tempo: 1,
+            tempo: 0,
+            half_turns: 0,
             white_castling: CastlingRights::new(),
             black_castling: CastlingRights::new(),
             castling_details: CastlingDetails::new(),
             en_passant: None,
+            white_checks: 0,
+            black_checks: 0,
+            white_pocket: Pocket::new(),
+            black_pocket: Pocket::new(),
+            promoted: Mask::nil(),
+            sub_turn: SubTurn::default(),
         }
     }
 
@@ -75,8 +214,10 @@ impl Metadata {
 
     pub fn empty() -> Metadata {
         Self {
+            variant: Variant::Standard,
             to_move: Color::White,
             tempo: 0,
+            half_turns: 0,
             white_castling: CastlingRights {
                 ooo: false,
                 oo: false,
@@ -87,6 +228,12 @@ impl Metadata {
             },
             castling_details: CastlingDetails::new(),
             en_passant: None,
+            white_checks: 0,
+            black_checks: 0,
+            white_pocket: Pocket::new(),
+            black_pocket: Pocket::new(),
+            promoted: Mask::nil(),
+            sub_turn: SubTurn::default(),
         }
     }
 }
@@ -1,14 +1,21 @@
 pub mod boardmap;
 pub mod castling;
 pub mod enums;
+pub mod explosions;
+pub mod fog;
 pub mod forced_draws;
 pub mod half;
 pub mod masks;
 pub mod metadata;
 pub mod moves;
 pub mod notation;
+pub mod odds;
 pub mod pieces;
+pub mod pockets;
+pub mod random;
+pub mod rules;
 pub mod squares;
+pub mod variants;
 pub mod zobrist;
 
 use std::{collections::HashSet, hash::Hash, sync::LazyLock};
@@ -16,11 +23,12 @@ use std::{collections::HashSet, hash::Hash, sync::LazyLock};
 use crate::shessboard::{
     boardmap::BoardMap,
     castling::{CastlingDetail, CastlingDetails, CastlingInfo, CastlingRights},
-    enums::{Color, ColorPiece, File, Piece, Rank},
+    enums::{Color, ColorPiece, File, Piece, Rank, Variant},
+    explosions::Explosion,
     forced_draws::{LastChange, ThreefoldRule},
     half::HalfBitBoard,
     masks::Mask,
-    metadata::Metadata,
+    metadata::{Metadata, SubTurn},
     moves::Move,
     pieces::{
         Millipawns, P,
@@ -43,6 +51,18 @@ pub struct BitBoard {
     pub black: HalfBitBoard,
 }
 
+/// What `apply` cannot read back from the move when it is undone. Like
+/// `Move::prev_epc`, it is history rather than position, so the caller keeps
+/// it for as long as it may want to undo.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Undo {
+    /// Pieces blown up by an atomic capture.
+    explosion: Option<Explosion>,
+    /// The captured piece had been promoted.
+    demoted: bool,
+    sub_turn: SubTurn,
+}
+
 #[test]
 fn bitboard_size() {
     dbg!(
@@ -54,6 +74,12 @@ fn bitboard_size() {
     );
 }
 
+impl Default for BitBoard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl BitBoard {
     pub fn new() -> Self {
         use Piece::*;
@@ -86,7 +112,7 @@ impl BitBoard {
         board[48..56].fill(Some(ColorPiece::BlackPawn));
 
         let mut board = BoardMap::new(board);
-        return Self::new_board(&board, metadata);
+        Self::new_board(&board, metadata)
     }
 
     pub fn new_board(board: &BoardMap<Option<ColorPiece>>, metadata: Metadata) -> Self {
@@ -148,23 +174,114 @@ impl BitBoard {
     }
 
     pub fn is_in_check(&self, c: Color) -> bool {
-        (self.color(c).kings.as_mask()
-            & self.color(c.other()).threats(
-                self.metadata.to_move.other(),
-                self.active().as_mask(),
-                None,
-            ))
+        self.royal_attacked(c, Piece::King)
+    }
+
+    /// Whether any `royal` piece of `c` is attacked.
+    pub fn royal_attacked(&self, c: Color, royal: Piece) -> bool {
+        (self.color(c).piece_mask(royal)
+            & self
+                .color(c.other())
+                .threats(c.other(), self.color(c).as_mask(), None))
         .any()
     }
 
+    /// Whether `mv` leaves a `royal` piece of the mover attacked.
+    pub fn exposes(&self, mv: Move, royal: Piece) -> bool {
+        let color = mv.color_and_piece.color();
+        let mut active = self.color(color).clone();
+        let mut passive = self.color(color.other()).clone();
+        Self::move_pieces(
+            &mut active,
+            &mut passive,
+            mv,
+            self.metadata.castling_details,
+        );
+
+        (active.piece_mask(royal) & passive.threats(color.other(), active.as_mask(), None)).any()
+    }
+
     pub fn sufficient_checkmating_materiel(&self) -> bool {
         self.white.has_sufficient_materiel() || self.black.has_sufficient_materiel()
     }
+
+    pub fn color_flip(&self) -> Self {
+        Self {
+            metadata: self.metadata.color_flip(),
+            white: self.black.mirror(),
+            black: self.white.mirror(),
+        }
+    }
+
+    pub fn reflect(&self) -> Option<Self> {
+        Some(Self {
+            metadata: self.metadata.reflect()?,
+            white: self.white.reflect(),
+            black: self.black.reflect(),
+        })
+    }
+
+    pub fn perft(&self, depth: usize) -> usize {
+        let mut moves = Vec::with_capacity(50);
+        self.generate_moves(&mut moves);
+
+        if depth <= 1 {
+            return if depth == 1 { moves.len() } else { 1 };
+        }
+
+        let mut sum = 0;
+        for mv in moves {
+            let mut b = self.clone();
+            b.apply(mv);
+            sum += b.perft(depth - 1);
+        }
+        sum
+    }
+}
+
+#[test]
+fn starting_position_perft() {
+    let board = BitBoard::new();
+    assert_eq!(board.perft(1), 20);
+    assert_eq!(board.perft(2), 400);
+    assert_eq!(board.perft(3), 8_902);
+}
+
+#[test]
+fn transforms_preserve_perft() {
+    use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    let mut rng = StdRng::from_seed(*b"3.141592653589793238462643383279");
+    let mut board = BitBoard::new();
+    let mut moves = vec![];
+    let mut reflected = 0;
+
+    for _ in 0..120 {
+        moves.clear();
+        board.generate_moves(&mut moves);
+        let Some(&mv) = moves.choose(&mut rng) else {
+            break;
+        };
+        board.apply(mv);
+
+        let flipped = board.color_flip();
+        assert_eq!(flipped.color_flip(), board);
+        assert_eq!(flipped.perft(2), board.perft(2));
+
+        if let Some(reflection) = board.reflect() {
+            assert_eq!(reflection.reflect().as_ref(), Some(&board));
+            assert_eq!(reflection.perft(2), board.perft(2));
+            reflected += 1;
+        }
+    }
+
+    assert!(reflected > 0);
 }
 
 impl BitBoard {
-    pub fn apply(&mut self, mv: Move) {
+    pub fn apply(&mut self, mv: Move) -> Undo {
         let (color, piece) = mv.color_and_piece.split();
+        let sub_turn = self.metadata.sub_turn;
 
         // update metadata
         self.metadata.to_move = color.other();
@@ -182,59 +299,98 @@ impl BitBoard {
 
         // calculate changes to board
         let (active, passive) = self.color_mut(color);
+        Self::move_pieces(active, passive, mv, details);
 
-        if let Some((sq, piece)) = mv.capture {
-            *passive.piece_mask_mut(piece) ^= sq.as_mask();
+        self.metadata.half_turns += 1;
+
+        Undo {
+            explosion: None,
+            demoted: false,
+            sub_turn,
         }
+    }
 
-        if let Some(p) = mv.promotion {
-            *active.piece_mask_mut(Piece::Pawn) ^= mv.from_to.from.as_mask();
-            *active.piece_mask_mut(p) ^= mv.from_to.to.as_mask();
-        } else if let Some(castling::CastlingSide::OOO) = mv.castling {
-            *active.piece_mask_mut(Piece::Rook) ^= details
-                .ooo
-                .rook_move
-                .as_move(color.starting_rank())
-                .as_mask();
-            *active.piece_mask_mut(Piece::King) ^= details
-                .ooo
-                .king_move
-                .as_move(color.starting_rank())
-                .as_mask()
-        } else if let Some(castling::CastlingSide::OO) = mv.castling {
-            *active.piece_mask_mut(Piece::Rook) ^= details
-                .oo
-                .rook_move
-                .as_move(color.starting_rank())
-                .as_mask();
-            *active.piece_mask_mut(Piece::King) ^= details
-                .oo
-                .king_move
-                .as_move(color.starting_rank())
-                .as_mask()
+    /// Returns whether the captured piece had been promoted. Captures go to
+    /// the mover's own pocket unless `to_partner`, as in bughouse, where the
+    /// partner's board takes them.
+    fn update_pockets(&mut self, mv: Move, to_partner: bool) -> bool {
+        let (color, piece) = mv.color_and_piece.split();
+        let pocketed = self.pocketed(mv);
+        let metadata = &mut self.metadata;
+
+        if mv.is_drop() {
+            metadata.pocket_mut(color).remove(piece);
+        }
+
+        let demoted = mv
+            .capture
+            .is_some_and(|(sq, _)| metadata.promoted.contains(sq));
+        if let Some((sq, _)) = mv.capture
+            && demoted
+        {
+            metadata.promoted = metadata.promoted.unset(sq);
+        }
+
+        if let Some(p) = pocketed
+            && !to_partner
+        {
+            metadata.pocket_mut(color).add(p);
+        }
+
+        if mv.promotion.is_some() {
+            metadata.promoted = metadata.promoted.set(mv.from_to.to);
+        } else if !mv.is_drop() && metadata.promoted.contains(mv.from_to.from) {
+            metadata.promoted ^= mv.from_to.as_mask();
+        }
+
+        demoted
+    }
+
+    fn downdate_pockets(&mut self, mv: Move, demoted: bool, to_partner: bool) {
+        let (color, piece) = mv.color_and_piece.split();
+        let metadata = &mut self.metadata;
+
+        if mv.promotion.is_some() {
+            metadata.promoted = metadata.promoted.unset(mv.from_to.to);
+        } else if !mv.is_drop() && metadata.promoted.contains(mv.from_to.to) {
+            metadata.promoted ^= mv.from_to.as_mask();
+        }
+
+        if let Some((sq, _)) = mv.capture
+            && demoted
+        {
+            metadata.promoted = metadata.promoted.set(sq);
+        }
+
+        if let Some(p) = self.pocketed(mv)
+            && !to_partner
+        {
+            self.metadata.pocket_mut(color).remove(p);
+        }
+
+        if mv.is_drop() {
+            self.metadata.pocket_mut(color).add(piece);
+        }
+    }
+
+    /// The piece a capture puts in hand, which is a pawn for promoted pieces.
+    pub fn pocketed(&self, mv: Move) -> Option<Piece> {
+        let (sq, p) = mv.capture?;
+        if self.metadata.promoted.contains(sq) {
+            Some(Piece::Pawn)
         } else {
-            *active.piece_mask_mut(mv.color_and_piece.piece()) ^= mv.from_to.as_mask();
+            Some(p)
         }
     }
 
-    pub fn undo(&mut self, mv: Move) {
+    pub fn undo(&mut self, mv: Move, undo: &Undo) {
         let (color, piece) = mv.color_and_piece.split();
 
         // update metadata
+        self.metadata.half_turns -= 1;
         self.metadata.to_move = color;
         self.metadata.tempo -= 1;
-        self.metadata.en_passant = if let Some((capture, Piece::Pawn)) = mv.capture {
-            if piece == Piece::Pawn && mv.from_to.to != capture {
-                Some(EnPassant {
-                    to: mv.from_to.to,
-                    capture,
-                })
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+        self.metadata.en_passant = mv.prev_epc.map(|to| EnPassant { to });
 
         // calculate changes to castling rights
         let details = self.metadata.castling_details;
@@ -247,6 +403,21 @@ impl BitBoard {
 
         // calculate changes to board
         let (active, passive) = self.color_mut(color);
+        Self::move_pieces(active, passive, mv, details);
+    }
+
+    fn move_pieces(
+        active: &mut HalfBitBoard,
+        passive: &mut HalfBitBoard,
+        mv: Move,
+        details: CastlingDetails,
+    ) {
+        let color = mv.color_and_piece.color();
+
+        if mv.is_drop() {
+            *active.piece_mask_mut(mv.color_and_piece.piece()) ^= mv.from_to.to.as_mask();
+            return;
+        }
 
         if let Some((sq, piece)) = mv.capture {
             *passive.piece_mask_mut(piece) ^= sq.as_mask();
@@ -261,7 +432,7 @@ impl BitBoard {
                 .rook_move
                 .as_move(color.starting_rank())
                 .as_mask();
-            *active.piece_mask_mut(Piece::King) ^= details
+            *active.piece_mask_mut(mv.color_and_piece.piece()) ^= details
                 .ooo
                 .king_move
                 .as_move(color.starting_rank())
@@ -272,7 +443,7 @@ impl BitBoard {
                 .rook_move
                 .as_move(color.starting_rank())
                 .as_mask();
-            *active.piece_mask_mut(Piece::King) ^= details
+            *active.piece_mask_mut(mv.color_and_piece.piece()) ^= details
                 .oo
                 .king_move
                 .as_move(color.starting_rank())
@@ -283,63 +454,393 @@ impl BitBoard {
     }
 
     pub fn generate_moves(&self, res: &mut Vec<Move>) {
-        let active_mask = self.active().as_mask();
-        let passive_mask = self.passive().as_mask();
+        self.generate_moves_by(res, |piece, from, res| {
+            self.generate_piece_moves(piece, from, res)
+        });
+    }
+
+    /// Every move, with the moves of each kind of piece generated by
+    /// `piece_moves`, which is how rules change how pieces move.
+    pub fn generate_moves_by(
+        &self,
+        res: &mut Vec<Move>,
+        piece_moves: impl Fn(Piece, Mask, &mut Vec<Move>),
+    ) {
+        use Piece::*;
+
+        for piece in [Queen, Rook, Bishop, Knight, Pawn, King] {
+            piece_moves(piece, self.active().piece_mask(piece), res);
+        }
+    }
+
+    /// Captures and queen promotions only, for quiescence search.
+    pub fn generate_captures(&self, res: &mut Vec<Move>) {
+        self.generate_captures_by(res, |piece, from, res| {
+            self.generate_piece_moves(piece, from, res)
+        });
+    }
+
+    /// As `generate_captures`, through `piece_moves` as in
+    /// `generate_moves_by`. Pieces that attack no enemy piece are skipped
+    /// without generating their moves.
+    pub fn generate_captures_by(
+        &self,
+        res: &mut Vec<Move>,
+        piece_moves: impl Fn(Piece, Mask, &mut Vec<Move>),
+    ) {
+        use Piece::*;
+        let start = res.len();
+        let blockers = self.as_mask();
+        let targets = self.passive().as_mask();
+
+        for piece in [Queen, Rook, Bishop, Knight, King] {
+            for sq in self.active().piece_mask(piece) {
+                let attacks = match piece {
+                    Queen => Queens::new(sq.as_mask()).threats(blockers),
+                    Rook => Rooks::new(sq.as_mask()).threats(blockers),
+                    Bishop => Bishops::new(sq.as_mask()).threats(blockers),
+                    Knight => Knights::MOVES.at(sq),
+                    _ => Kings::new(sq.as_mask()).threats(),
+                };
+                if attacks.overlap(targets).any() {
+                    piece_moves(piece, sq.as_mask(), res);
+                }
+            }
+        }
+        // en passant and promotions do not need a target
+        piece_moves(Pawn, self.active().pawns.as_mask(), res);
+
+        retain_from(res, start, |mv| {
+            mv.capture.is_some() || mv.promotion == Some(Queen)
+        });
+    }
+
+    pub fn generate_drops(&self, res: &mut Vec<Move>) {
         let color = self.metadata.to_move;
+        let pocket = self.metadata.pocket(color);
+        if pocket.is_empty() {
+            return;
+        }
 
-        self.active().queens.enumerate_legal_moves(
-            color,
-            active_mask,
-            self.passive(),
-            passive_mask,
-            self.active().kings,
-            res,
-        );
+        let prev_epc = self.metadata.en_passant.map(|ep| ep.to);
+        let king = self.active().kings.as_mask();
+        let in_check = self.is_in_check(color);
+        let back_ranks = Rank::_1.as_mask() | Rank::_8.as_mask();
+
+        for sq in !self.as_mask() {
+            // only a drop that blocks the check can be legal
+            if in_check
+                && self
+                    .passive()
+                    .threats(color.other(), self.active().as_mask().set(sq), None)
+                    .overlap(king)
+                    .any()
+            {
+                continue;
+            }
 
-        self.active().rooks.enumerate_legal_moves(
-            color,
-            active_mask,
-            self.passive(),
-            passive_mask,
-            self.active().kings,
-            res,
-        );
+            for piece in pocket.pieces() {
+                if piece == Piece::Pawn && back_ranks.contains(sq) {
+                    continue;
+                }
+                res.push(Move::drop(ColorPiece::new(color, piece), sq, prev_epc));
+            }
+        }
+    }
 
-        self.active().bishops.enumerate_legal_moves(
-            color,
-            active_mask,
-            self.passive(),
-            passive_mask,
-            self.active().kings,
-            res,
-        );
+    pub fn has_legal_move(&self) -> bool {
+        self.has_legal_move_by(|piece, from, res| self.generate_piece_moves(piece, from, res))
+    }
 
-        self.active().knights.enumerate_legal_moves(
-            color,
-            active_mask,
-            self.passive(),
-            self.active().kings,
-            res,
-        );
+    /// As `has_legal_move`, through `piece_moves` as in `generate_moves_by`.
+    pub fn has_legal_move_by(&self, piece_moves: impl Fn(Piece, Mask, &mut Vec<Move>)) -> bool {
+        use Piece::*;
+        let mut res = Vec::with_capacity(8);
+        for piece in [King, Knight, Pawn, Bishop, Rook, Queen] {
+            for sq in self.active().piece_mask(piece) {
+                piece_moves(piece, sq.as_mask(), &mut res);
+                if !res.is_empty() {
+                    return true;
+                }
+            }
+        }
+        false
+    }
 
-        self.active().pawns.enumerate_legal_moves(
-            color,
-            active_mask,
-            passive_mask,
+    pub fn is_checkmate(&self) -> bool {
+        self.is_in_check(self.metadata.to_move) && !self.has_legal_move()
+    }
+
+    pub fn is_stalemate(&self) -> bool {
+        !self.is_in_check(self.metadata.to_move) && !self.has_legal_move()
+    }
+
+    pub fn gives_check(&self, mv: Move) -> bool {
+        self.checks_royal(mv, Piece::King)
+    }
+
+    /// Whether `mv` attacks an opposing `royal` piece, either with the piece
+    /// as it lands or by uncovering a slider behind the square it leaves.
+    pub fn checks_royal(&self, mv: Move, royal: Piece) -> bool {
+        let (color, piece) = mv.color_and_piece.split();
+        let active = self.color(color);
+        let Some(king) = self.color(color.other()).piece_mask(royal).first() else {
+            return false;
+        };
+
+        // squares the move empties and the pieces it puts down
+        let (mut vacated, mut landed) = (mv.from_to.from.as_mask(), Mask::nil());
+        let mut occupied = self.as_mask().unset(mv.from_to.from).set(mv.from_to.to);
+        if let Some((sq, _)) = mv.capture {
+            occupied = occupied.unset(sq).set(mv.from_to.to);
+        }
+        if let Some(side) = mv.castling {
+            let details = self.metadata.castling_details.select(side);
+            let rook = details.rook_move.as_move(color.starting_rank());
+            occupied = occupied.unset(rook.from).set(rook.to);
+            vacated = vacated.set(rook.from);
+            landed = landed.set(rook.to);
+        }
+        let piece = mv.promotion.unwrap_or(piece);
+
+        let attacks = |piece: Piece, sq: Square| match piece {
+            Piece::Pawn => Pawns::new(sq.as_mask()).threats(color),
+            Piece::Knight => Knights::MOVES.at(sq),
+            Piece::Bishop => Bishops::new(sq.as_mask()).threats(occupied),
+            Piece::Rook => Rooks::new(sq.as_mask()).threats(occupied),
+            Piece::Queen => {
+                Rooks::new(sq.as_mask()).threats(occupied)
+                    | Bishops::new(sq.as_mask()).threats(occupied)
+            }
+            Piece::King => Kings::new(sq.as_mask()).threats(),
+        };
+        if attacks(piece, mv.from_to.to).contains(king)
+            || landed.any() && attacks(Piece::Rook, landed.first().unwrap()).contains(king)
+        {
+            return true;
+        }
+
+        // only sliders that stay put can be uncovered
+        let straight = (active.rooks.as_mask() | active.queens.as_mask()) & !vacated;
+        let diagonal = (active.bishops.as_mask() | active.queens.as_mask()) & !vacated;
+        (Rooks::new(king.as_mask()).threats(occupied) & straight).any()
+            || (Bishops::new(king.as_mask()).threats(occupied) & diagonal).any()
+    }
+
+    pub fn generate_piece_moves(&self, piece: Piece, from: Mask, res: &mut Vec<Move>) {
+        self.generate_guarded_moves(piece, from, self.active().kings, Pawns::PROMOTIONS, res);
+    }
+
+    /// Moves of the `piece`s on `from` that leave none of `royal` attacked,
+    /// which `Kings::nil()` turns off, with pawns promoting to `promotions`.
+    /// Kings move as royal kings whatever `royal` is.
+    pub fn generate_guarded_moves(
+        &self,
+        piece: Piece,
+        from: Mask,
+        royal: Kings,
+        promotions: &[Piece],
+        res: &mut Vec<Move>,
+    ) {
+        let active_mask = self.active().as_mask();
+        let passive_mask = self.passive().as_mask();
+        let color = self.metadata.to_move;
+        let prev_epc = self.metadata.en_passant.map(|ep| ep.to);
+
+        match piece {
+            Piece::Queen => Queens::new(from).enumerate_legal_moves(
+                color,
+                active_mask,
+                self.passive(),
+                passive_mask,
+                royal,
+                prev_epc,
+                res,
+            ),
+            Piece::Rook => Rooks::new(from).enumerate_legal_moves(
+                color,
+                active_mask,
+                self.passive(),
+                passive_mask,
+                royal,
+                prev_epc,
+                res,
+            ),
+            Piece::Bishop => Bishops::new(from).enumerate_legal_moves(
+                color,
+                active_mask,
+                self.passive(),
+                passive_mask,
+                royal,
+                prev_epc,
+                res,
+            ),
+            Piece::Knight => Knights::new(from).enumerate_legal_moves(
+                color,
+                active_mask,
+                self.passive(),
+                royal,
+                prev_epc,
+                res,
+            ),
+            Piece::Pawn => Pawns::new(from).enumerate_legal_moves(
+                color,
+                active_mask,
+                passive_mask,
+                self.passive(),
+                self.metadata.en_passant,
+                royal,
+                promotions,
+                res,
+            ),
+            Piece::King => Kings::new(from).enumerate_legal_moves(
+                color,
+                active_mask,
+                passive_mask,
+                self.passive(),
+                self.metadata.castling_rights(color).0,
+                self.metadata.castling_details,
+                prev_epc,
+                res,
+            ),
+        }
+    }
+
+    /// Moves of kings on `from` that are not royal, which may step into
+    /// attacks and do not castle.
+    pub fn generate_commoner_moves(&self, from: Mask, res: &mut Vec<Move>) {
+        Kings::new(from).enumerate_commoner_moves(
+            self.metadata.to_move,
+            self.active().as_mask(),
             self.passive(),
-            self.metadata.en_passant,
-            self.active().kings,
+            self.metadata.en_passant.map(|ep| ep.to),
             res,
         );
+    }
 
-        self.active().kings.enumerate_legal_moves(
+    /// Castling moves for the `piece` on `from`, which castles as the king.
+    pub fn generate_castling_moves(&self, piece: Piece, from: Mask, res: &mut Vec<Move>) {
+        let color = self.metadata.to_move;
+        Kings::new(from).enumerate_castling_moves(
+            piece,
             color,
-            active_mask,
-            passive_mask,
+            self.active().as_mask(),
+            self.passive().as_mask(),
             self.passive(),
             self.metadata.castling_rights(color).0,
             self.metadata.castling_details,
+            self.metadata.en_passant.map(|ep| ep.to),
             res,
         );
     }
 }
+
+/// Keeps only the moves from `start` onwards that satisfy `keep`.
+fn retain_from(res: &mut Vec<Move>, start: usize, keep: impl Fn(&Move) -> bool) {
+    let mut n = start;
+    for i in start..res.len() {
+        if keep(&res[i]) {
+            res[n] = res[i];
+            n += 1;
+        }
+    }
+    res.truncate(n);
+}
+
+#[test]
+fn gives_check_and_mates() {
+    use crate::shessboard::rules::Rules;
+    use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    let mut rng = StdRng::from_seed(*b"3.141592653589793238462643383279");
+    let mut moves = vec![];
+    let mut checks = 0;
+
+    // castling, en passant, drops and royal knights all give check
+    // differently
+    let variants = [
+        Variant::Standard,
+        Variant::Crazyhouse,
+        Variant::Knightmate,
+        Variant::ThreeCheck,
+    ];
+    for i in 0..20 {
+        let variant = variants[i % variants.len()];
+        let mut board = variant.start();
+        for _ in 0..200 {
+            moves.clear();
+            variant.generate_moves(&board, &mut moves);
+            assert_eq!(variant.has_legal_move(&board), !moves.is_empty());
+
+            for &mv in &moves {
+                let mut b = board.clone();
+                variant.apply(&mut b, mv);
+                let check = variant.gives_check(&board, mv);
+                assert_eq!(check, variant.is_in_check(&b, b.metadata.to_move));
+                checks += check as usize;
+            }
+
+            let Some(&mv) = moves.choose(&mut rng) else {
+                break;
+            };
+            variant.apply(&mut board, mv);
+        }
+    }
+
+    assert!(checks > 0);
+
+    let mut board = BitBoard::new();
+    for pm in [
+        Square::f2.to(Square::f3),
+        Square::e7.to(Square::e5),
+        Square::g2.to(Square::g4),
+    ] {
+        moves.clear();
+        board.generate_moves(&mut moves);
+        board.apply(*moves.iter().find(|m| m.from_to == pm).unwrap());
+    }
+    moves.clear();
+    board.generate_moves(&mut moves);
+    let mate = *moves
+        .iter()
+        .find(|m| m.from_to == Square::d8.to(Square::h4))
+        .unwrap();
+    assert!(board.gives_check(mate));
+    assert_eq!(Variant::Standard.suffix(&board, mate), "#");
+    board.apply(mate);
+    assert!(board.is_checkmate());
+    assert!(!board.is_stalemate());
+}
+
+#[test]
+fn captures_only() {
+    use crate::shessboard::rules::Rules;
+    use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    let mut rng = StdRng::from_seed(*b"3.141592653589793238462643383279");
+    let (mut moves, mut captures) = (vec![], vec![]);
+
+    for variant in [Variant::Standard, Variant::Atomic, Variant::Knightmate] {
+        for _ in 0..10 {
+            let mut board = variant.start();
+            for _ in 0..150 {
+                moves.clear();
+                captures.clear();
+                variant.generate_moves(&board, &mut moves);
+                variant.generate_captures(&board, &mut captures);
+
+                moves.retain(|mv| mv.capture.is_some() || mv.promotion == Some(Piece::Queen));
+                moves.sort_by_cached_key(|mv| format!("{mv:?}"));
+                captures.sort_by_cached_key(|mv| format!("{mv:?}"));
+                assert_eq!(captures, moves);
+
+                moves.clear();
+                variant.generate_moves(&board, &mut moves);
+                let Some(&mv) = moves.choose(&mut rng) else {
+                    break;
+                };
+                variant.apply(&mut board, mv);
+            }
+        }
+    }
+}
@@ -48,6 +48,10 @@ impl ProtoMove {
         passive: &HalfBitBoard,
         passive_color: Color,
     ) -> bool {
+        if !king.as_mask().any() {
+            return false;
+        }
+
         passive
             .threats(passive_color, active ^ self.as_mask(), capture)
             .overlap(king.as_mask())
@@ -202,7 +206,6 @@ fn en_passant_into_check() {
     board.metadata.to_move = Color::Black;
     board.metadata.en_passant = Some(EnPassant {
         to: Square::at(File::E, Rank::_3),
-        capture: Square::at(File::E, Rank::_4),
     });
 
     board.white.pawns = Pawns::new(Square::at(File::E, Rank::_4).as_mask());
@@ -236,13 +239,35 @@ fn en_passant_into_check() {
     assert_eq!(not.find(&moves), vec![]);
 }
 
+#[test]
+fn rook_captured_at_home() {
+    let mut board = BitBoard::new();
+    board.set_piece(None, Square::at(File::G, Rank::_7));
+    board.set_piece(Some(ColorPiece::WhiteBishop), Square::at(File::B, Rank::_2));
+
+    let mut moves = vec![];
+    board.generate_moves(&mut moves);
+    let bxh8 = *moves
+        .iter()
+        .find(|mv| mv.from_to.to == Square::at(File::H, Rank::_8))
+        .unwrap();
+    assert_eq!(bxh8.color_and_piece, ColorPiece::WhiteBishop);
+
+    board.apply(bxh8);
+    assert!(!board.metadata.black_castling.oo);
+    assert!(board.metadata.black_castling.ooo);
+}
+
 impl Display for ProtoMove {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}{}", self.from, self.to)
     }
 }
 
+/// Aligned like a `u64`, as `size_fuckery` requires, so that moves can be
+/// copied and compared as single words.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(align(8))]
 pub struct Move {
     pub color_and_piece: ColorPiece,
     pub from_to: ProtoMove,
@@ -253,31 +278,45 @@ pub struct Move {
 }
 
 impl Move {
+    /// Drops are encoded as moves that start and end on the same square.
+    pub const fn drop(color_and_piece: ColorPiece, to: Square, prev_epc: Option<Square>) -> Self {
+        Self {
+            color_and_piece,
+            from_to: ProtoMove { from: to, to },
+            castling: None,
+            capture: None,
+            prev_epc,
+            promotion: None,
+        }
+    }
+
+    pub fn is_drop(&self) -> bool {
+        self.from_to.from == self.from_to.to
+    }
+
     pub fn en_passant_square(&self) -> Option<EnPassant> {
         if self.color_and_piece == ColorPiece::WhitePawn {
             if let ((f, Rank::_2), Rank::_4) =
                 (self.from_to.from.algebraic(), self.from_to.to.rank())
             {
-                return Some(EnPassant {
+                Some(EnPassant {
                     to: Square::at(f, Rank::_3),
-                    capture: Square::at(f, Rank::_4),
-                });
+                })
             } else {
-                return None;
-            };
+                None
+            }
         } else if self.color_and_piece == ColorPiece::BlackPawn {
             if let ((f, Rank::_7), Rank::_5) =
                 (self.from_to.from.algebraic(), self.from_to.to.rank())
             {
-                return Some(EnPassant {
+                Some(EnPassant {
                     to: Square::at(f, Rank::_6),
-                    capture: Square::at(f, Rank::_5),
-                });
+                })
             } else {
-                return None;
-            };
+                None
+            }
         } else {
-            return None;
+            None
         }
     }
 
@@ -293,7 +332,8 @@ impl Move {
             oo: true,
         };
 
-        if piece == Piece::King {
+        // whichever piece is royal starts on the king's square
+        if self.from_to.from == Square::at(details.oo.king_move.from, color.starting_rank()) {
             active.ooo = false;
             active.oo = false;
         } else if piece == Piece::Rook {
@@ -307,16 +347,14 @@ impl Move {
         }
 
         if let Some((sq, Piece::Rook)) = self.capture {
-            if piece == Piece::Rook {
-                let color = color.other();
+            let color = color.other();
 
-                if sq == Square::at(details.ooo.rook_move.from, color.starting_rank()) {
-                    passive.ooo = false;
-                }
+            if sq == Square::at(details.ooo.rook_move.from, color.starting_rank()) {
+                passive.ooo = false;
+            }
 
-                if sq == Square::at(details.oo.rook_move.from, color.starting_rank()) {
-                    passive.oo = false;
-                }
+            if sq == Square::at(details.oo.rook_move.from, color.starting_rank()) {
+                passive.oo = false;
             }
         }
 
@@ -326,6 +364,9 @@ impl Move {
 
 impl Display for Move {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_drop() {
+            return write!(f, "{}@{}", self.color_and_piece.unicode(), self.from_to.to);
+        }
         write!(f, "{}{}", self.color_and_piece.unicode(), self.from_to)?;
         if let Some((sq, p)) = self.capture {
             write!(
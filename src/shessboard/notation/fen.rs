@@ -1,15 +1,354 @@
-use crate::shessboard::{BitBoard, boardmap::BoardMap, enums::ColorPiece};
+use std::fmt::Write;
 
-fn read_fen(s: &str) -> Option<(BitBoard, &str)> {
-    todo!()
+use crate::shessboard::{
+    BitBoard,
+    boardmap::BoardMap,
+    castling::CastlingRights,
+    enums::{Color, ColorPiece, File, Piece, Rank, Variant},
+    forced_draws::LastChange,
+    metadata::Metadata,
+    notation::{many, skip_char, try_to},
+    pieces::pawns::EnPassant,
+    pockets::Pocket,
+    squares::Square,
+};
+
+pub const STARTING_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Reads a position played as `variant`. Check counts and pockets are
+/// taken as written, and the variant's rules decide what they mean.
+pub fn read_fen(variant: Variant, s: &str) -> Option<((BitBoard, LastChange<'static>), &str)> {
+    let (board, s) = read_fen_board(s)?;
+    let (pockets, s) = try_to(s, read_fen_pockets);
+    let s = skip_char(' ', s)?.1;
+    let (to_move, s) = read_fen_color(s)?;
+    let s = skip_char(' ', s)?.1;
+    let ((white_castling, black_castling), s) = read_fen_castling(s)?;
+    let s = skip_char(' ', s)?.1;
+    let (en_passant, s) = read_fen_en_passant(s)?;
+    let (clocks, s) = try_to(s, read_fen_clocks);
+    let (half_moves, full_moves) = clocks.unwrap_or((0, 1));
+    let (checks, s) = try_to(s, read_fen_checks);
+
+    let mut metadata = Metadata::empty();
+    metadata.variant = variant;
+    metadata.to_move = to_move;
+    metadata.tempo = (full_moves.max(1) - 1) * 2 + (to_move == Color::Black) as u16;
+    metadata.half_turns = metadata.tempo;
+    metadata.white_castling = white_castling;
+    metadata.black_castling = black_castling;
+    metadata.en_passant = en_passant;
+
+    if let Some((white_checks, black_checks)) = checks {
+        metadata.white_checks = white_checks;
+        metadata.black_checks = black_checks;
+    }
+
+    if let Some((white_pocket, black_pocket)) = pockets {
+        metadata.white_pocket = white_pocket;
+        metadata.black_pocket = black_pocket;
+    }
+
+    let change = LastChange::Static(metadata.tempo.saturating_sub(half_moves));
+
+    Some(((BitBoard::new_board(&board, metadata), change), s))
 }
 
-fn read_fen_board(s: &str) -> Option<(BoardMap<Option<ColorPiece>>, &str)> {
-    todo!()
+pub fn read_fen_board(mut s: &str) -> Option<(BoardMap<Option<ColorPiece>>, &str)> {
+    let mut res = BoardMap::new_with(None);
+
+    for rank in (0..8).rev() {
+        let rank = Rank::rank(rank)?;
+        if rank != Rank::_8 {
+            s = skip_char('/', s)?.1;
+        }
+
+        let (pieces, ss) = read_fen_rank(s)?;
+        s = ss;
+
+        for (file, p) in pieces.into_iter().enumerate() {
+            res.set(Square::at(File::file(file as i8)?, rank), p);
+        }
+    }
+
+    Some((res, s))
 }
 
-fn read_fen_rank(s: &str) -> Option<([Option<ColorPiece>; 8], &str)> {
-    todo!()
+pub fn read_fen_rank(s: &str) -> Option<([Option<ColorPiece>; 8], &str)> {
+    let mut res = [None; 8];
+    let mut file = 0;
+    let mut cs = s.chars();
+
+    while file < 8 {
+        let c = cs.next()?;
+        if let Some(n) = c.to_digit(10) {
+            if !(1..=8 - file as u32).contains(&n) {
+                return None;
+            }
+            file += n as usize;
+        } else {
+            res[file] = Some(ColorPiece::from_char(c)?);
+            file += 1;
+        }
+    }
+
+    Some((res, cs.as_str()))
 }
 
-// fn read_fen_metadata_normal(s: &str) -> Option<>
+/// Crazyhouse pockets in the `[QNp]` form, white pieces in upper case.
+pub fn read_fen_pockets(s: &str) -> Option<((Pocket, Pocket), &str)> {
+    let s = skip_char('[', s)?.1;
+    let (pieces, s) = many(s, |s| {
+        let mut cs = s.chars();
+        Some((ColorPiece::from_char(cs.next()?)?, cs.as_str()))
+    });
+    let s = skip_char(']', s)?.1;
+
+    let mut white = Pocket::new();
+    let mut black = Pocket::new();
+    for p in pieces {
+        if p.piece() == Piece::King {
+            return None;
+        }
+        match p.color() {
+            Color::White => white.add(p.piece()),
+            Color::Black => black.add(p.piece()),
+        }
+    }
+
+    Some(((white, black), s))
+}
+
+pub fn read_fen_color(s: &str) -> Option<(Color, &str)> {
+    if let Some((_, s)) = skip_char('w', s) {
+        Some((Color::White, s))
+    } else if let Some((_, s)) = skip_char('b', s) {
+        Some((Color::Black, s))
+    } else {
+        None
+    }
+}
+
+pub fn read_fen_castling(s: &str) -> Option<((CastlingRights, CastlingRights), &str)> {
+    let mut white = CastlingRights {
+        ooo: false,
+        oo: false,
+    };
+    let mut black = white;
+
+    if let Some((_, s)) = skip_char('-', s) {
+        return Some(((white, black), s));
+    }
+
+    let (k, s) = try_to(s, |s| skip_char('K', s));
+    let (q, s) = try_to(s, |s| skip_char('Q', s));
+    let (kk, s) = try_to(s, |s| skip_char('k', s));
+    let (qq, s) = try_to(s, |s| skip_char('q', s));
+
+    white.oo = k.is_some();
+    white.ooo = q.is_some();
+    black.oo = kk.is_some();
+    black.ooo = qq.is_some();
+
+    if white.oo || white.ooo || black.oo || black.ooo {
+        Some(((white, black), s))
+    } else {
+        None
+    }
+}
+
+pub fn read_fen_en_passant(s: &str) -> Option<(Option<EnPassant>, &str)> {
+    if let Some((_, s)) = skip_char('-', s) {
+        return Some((None, s));
+    }
+
+    let (to, s) = Square::read(s)?;
+    if let Rank::_3 | Rank::_6 = to.rank() {
+        Some((Some(EnPassant { to }), s))
+    } else {
+        None
+    }
+}
+
+pub fn read_fen_clocks(s: &str) -> Option<((u16, u16), &str)> {
+    let s = skip_char(' ', s)?.1;
+    let (half_moves, s) = read_number(s)?;
+    let s = skip_char(' ', s)?.1;
+    let (full_moves, s) = read_number(s)?;
+    Some(((half_moves, full_moves), s))
+}
+
+/// Three-check counters in the `+W+B` form, counting checks given by each side.
+pub fn read_fen_checks(s: &str) -> Option<((u8, u8), &str)> {
+    let s = skip_char(' ', s)?.1;
+    let s = skip_char('+', s)?.1;
+    let (white, s) = read_number(s)?;
+    let s = skip_char('+', s)?.1;
+    let (black, s) = read_number(s)?;
+    Some(((white.try_into().ok()?, black.try_into().ok()?), s))
+}
+
+fn read_number(s: &str) -> Option<(u16, &str)> {
+    let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    Some((s[..end].parse().ok()?, &s[end..]))
+}
+
+pub fn write_fen(board: &BitBoard, change: &LastChange) -> String {
+    let mut res = String::new();
+    let mut map = BoardMap::new_with(None);
+    board.render(&mut map);
+
+    for rank in (0..8).rev() {
+        let rank = Rank::rank(rank).unwrap();
+        let mut empty = 0;
+
+        for sq in rank.as_mask() {
+            if let Some(p) = map.at(sq) {
+                if empty > 0 {
+                    write!(res, "{empty}").unwrap();
+                    empty = 0;
+                }
+                res.push(p.letter());
+            } else {
+                empty += 1;
+            }
+        }
+
+        if empty > 0 {
+            write!(res, "{empty}").unwrap();
+        }
+        if rank != Rank::_1 {
+            res.push('/');
+        }
+    }
+
+    let metadata = &board.metadata;
+
+    if metadata.variant.has_drops() {
+        res.push('[');
+        res.push_str(&metadata.white_pocket.letters(Color::White));
+        res.push_str(&metadata.black_pocket.letters(Color::Black));
+        res.push(']');
+    }
+
+    res.push(' ');
+    res.push(match metadata.to_move {
+        Color::White => 'w',
+        Color::Black => 'b',
+    });
+    res.push(' ');
+
+    let castling = [
+        (metadata.white_castling.oo, 'K'),
+        (metadata.white_castling.ooo, 'Q'),
+        (metadata.black_castling.oo, 'k'),
+        (metadata.black_castling.ooo, 'q'),
+    ];
+    if castling.iter().any(|(c, _)| *c) {
+        for (c, l) in castling {
+            if c {
+                res.push(l);
+            }
+        }
+    } else {
+        res.push('-');
+    }
+
+    if let Some(ep) = metadata.en_passant {
+        write!(res, " {}", ep.to).unwrap();
+    } else {
+        res.push_str(" -");
+    }
+
+    write!(
+        res,
+        " {} {}",
+        metadata.tempo.saturating_sub(change.tempo()),
+        metadata.turn()
+    )
+    .unwrap();
+
+    if metadata.variant == Variant::ThreeCheck {
+        write!(res, " +{}+{}", metadata.white_checks, metadata.black_checks).unwrap();
+    }
+
+    res
+}
+
+#[test]
+fn fen_roundtrip() {
+    let ((board, change), "") = read_fen(Variant::Standard, STARTING_POSITION).unwrap() else {
+        panic!()
+    };
+    assert_eq!(board, BitBoard::new());
+    assert_eq!(write_fen(&board, &change), STARTING_POSITION);
+
+    for (variant, fen) in [
+        (
+            Variant::Standard,
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        ),
+        (
+            Variant::Standard,
+            "rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2",
+        ),
+        (Variant::Standard, "8/8/4k3/8/2R5/8/3K4/8 b - - 17 42"),
+        (
+            Variant::ThreeCheck,
+            "rnbqkb1r/pppp1ppp/5n2/4p2Q/4P3/8/PPPP1PPP/RNB1KBNR w KQkq - 2 3 +2+0",
+        ),
+        (
+            Variant::Crazyhouse,
+            "r1bqkb1r/pppp1ppp/2n5/4p3/4P3/8/PPPP1PPP/RNBQKB1R[Nn] w KQkq - 0 4",
+        ),
+        (
+            Variant::Crazyhouse,
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR[] w KQkq - 0 1",
+        ),
+    ] {
+        let ((board, change), "") = read_fen(variant, fen).unwrap() else {
+            panic!()
+        };
+        assert_eq!(board.metadata.variant, variant);
+        assert_eq!(write_fen(&board, &change), fen);
+    }
+
+    // the counters alone do not make a three-check game
+    let fen = "rnbqkb1r/pppp1ppp/5n2/4p2Q/4P3/8/PPPP1PPP/RNB1KBNR w KQkq - 2 3 +2+0";
+    let ((board, _), "") = read_fen(Variant::Standard, fen).unwrap() else {
+        panic!()
+    };
+    assert_eq!(board.metadata.variant, Variant::Standard);
+    assert_eq!(board.metadata.white_checks, 2);
+    assert_eq!(board.metadata.black_checks, 0);
+
+    assert!(
+        read_fen(
+            Variant::Standard,
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -"
+        )
+        .is_none()
+    );
+}
+
+#[test]
+fn known_positions_perft() {
+    for (fen, depth, nodes) in [
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            3,
+            97_862,
+        ),
+        ("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1", 4, 43_238),
+        (
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            3,
+            62_379,
+        ),
+    ] {
+        let ((board, _), "") = read_fen(Variant::Standard, fen).unwrap() else {
+            panic!()
+        };
+        assert_eq!(board.perft(depth), nodes, "{fen}");
+    }
+}
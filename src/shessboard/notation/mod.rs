@@ -1,20 +1,26 @@
-use std::{default, fmt::Display, str::FromStr};
+use std::{
+    default,
+    fmt::{Display, Write},
+    str::FromStr,
+};
 
 use crate::shessboard::{
     castling::{CastlingInfo, CastlingSide},
-    enums::{File, Piece, Rank},
+    enums::{Color, File, Piece, Rank},
     moves::Move,
     pieces,
     squares::Square,
 };
 
-mod fen;
+pub mod fen;
+pub mod pgn;
 mod uci;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Algebraic {
     Castling(CastlingSide),
     Normal(Normal),
+    Drop(Piece, Square),
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -29,11 +35,12 @@ pub struct Normal {
 
 impl Algebraic {
     pub fn new(mv: &Move, legal_moves: &[Move]) -> Self {
-        match mv.castling {
-            Some(pm) => {
-                return Self::Castling(pm);
-            }
-            None => {}
+        if mv.is_drop() {
+            return Self::Drop(mv.color_and_piece.piece(), mv.from_to.to);
+        }
+
+        if let Some(pm) = mv.castling {
+            return Self::Castling(pm);
         }
 
         let mut res = Normal {
@@ -78,7 +85,7 @@ impl Algebraic {
     }
 
     pub fn find(self, mv: &[Move]) -> Vec<Move> {
-        mv.into_iter()
+        mv.iter()
             .filter_map(|m| if self.matches(m) { Some(*m) } else { None })
             .collect::<Vec<_>>()
     }
@@ -87,7 +94,7 @@ impl Algebraic {
         match self {
             Algebraic::Castling(castling_side) => {
                 if let Some(pm) = mv.castling {
-                    castling_side == CastlingSide::OOO
+                    castling_side == pm
                 } else {
                     false
                 }
@@ -106,12 +113,18 @@ impl Algebraic {
                     && destination == mv.from_to.to
                     && promotion == mv.promotion
                     && capture == mv.capture.is_some()
+                    && !mv.is_drop()
+            }
+            Algebraic::Drop(piece, destination) => {
+                mv.is_drop() && mv.color_and_piece.piece() == piece && mv.from_to.to == destination
             }
         }
     }
 
     pub fn read(s: &str) -> Option<(Self, &str)> {
-        if let Some((n, s)) = Self::read_pawn_move(s) {
+        if let Some(((p, sq), s)) = Self::read_drop(s) {
+            Some((Self::Drop(p, sq), s))
+        } else if let Some((n, s)) = Self::read_pawn_move(s) {
             Some((Self::Normal(n), s))
         } else if let Some((n, s)) = Self::read_piece_move(s) {
             Some((Self::Normal(n), s))
@@ -122,6 +135,13 @@ impl Algebraic {
         }
     }
 
+    pub fn read_drop(s: &str) -> Option<((Piece, Square), &str)> {
+        let (piece, s) = Piece::read(s, false).or_else(|| Piece::read(s, true))?;
+        let s = skip_char('@', s)?.1;
+        let (destination, s) = Square::read(s)?;
+        Some(((piece, destination), s))
+    }
+
     pub fn read_pawn_move(s: &str) -> Option<(Normal, &str)> {
         let (origin_file, s) = try_to(s, Self::read_pawn_capture_preamble);
         let (destination, s) = Square::read(s)?;
@@ -224,6 +244,9 @@ impl Display for Algebraic {
         match self {
             Algebraic::Castling(CastlingSide::OOO) => write!(f, "O-O-O")?,
             Algebraic::Castling(CastlingSide::OO) => write!(f, "O-O")?,
+            Algebraic::Drop(piece, destination) => {
+                write!(f, "{}@{}", piece.white_letter(), destination)?;
+            }
             Algebraic::Normal(Normal {
                 piece,
                 origin_rank,
@@ -267,6 +290,40 @@ impl FromStr for Algebraic {
     }
 }
 
+/// A move as it was played, with its check suffix and the turn it belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Played {
+    pub turn: usize,
+    pub color: Color,
+    pub notation: (Algebraic, &'static str),
+}
+
+/// Writes a move list like `1. e4 e5 2. Nf3`. Moves that one side makes in
+/// the same turn, as in marseillais, are joined like `1. e4/d4 e5`.
+pub fn write_move_list(moves: &[Played]) -> String {
+    let mut res = String::new();
+    let mut prev: Option<&Played> = None;
+
+    for mv in moves {
+        let (n, suffix) = mv.notation;
+        match prev {
+            Some(p) if (p.turn, p.color) == (mv.turn, mv.color) => res.push('/'),
+            Some(p) if p.turn == mv.turn => res.push(' '),
+            _ => {
+                if prev.is_some() {
+                    res.push(' ');
+                }
+                let dots = if mv.color == Color::White { "." } else { "..." };
+                write!(res, "{}{dots} ", mv.turn).unwrap();
+            }
+        }
+        write!(res, "{n}{suffix}").unwrap();
+        prev = Some(mv);
+    }
+
+    res
+}
+
 fn try_to<F, T>(s: &str, mut f: F) -> (Option<T>, &str)
 where
     F: FnMut(&str) -> Option<(T, &str)>,
@@ -314,5 +371,31 @@ where
     F: FnMut(&str) -> Option<(T, &str)>,
 {
     let (res, s) = many(s, f);
-    if res.len() == 0 { None } else { Some((res, s)) }
+    if res.is_empty() { None } else { Some((res, s)) }
+}
+
+#[test]
+fn move_list_grouping() {
+    let played = |turn, color, s| Played {
+        turn,
+        color,
+        notation: (Algebraic::read(s).unwrap().0, ""),
+    };
+
+    let standard = [
+        played(1, Color::White, "e4"),
+        played(1, Color::Black, "e5"),
+        played(2, Color::White, "Nf3"),
+    ];
+    assert_eq!(write_move_list(&standard), "1. e4 e5 2. Nf3");
+    assert_eq!(write_move_list(&standard[1..]), "1... e5 2. Nf3");
+
+    let marseillais = [
+        played(1, Color::White, "e4"),
+        played(1, Color::White, "d4"),
+        played(1, Color::Black, "e5"),
+        played(1, Color::Black, "d5"),
+        played(2, Color::White, "Bb5"),
+    ];
+    assert_eq!(write_move_list(&marseillais), "1. e4/d4 e5/d5 2. Bb5");
 }
@@ -0,0 +1,88 @@
+use std::fmt::Write;
+
+use crate::shessboard::{
+    BitBoard,
+    enums::{GameEnd, Variant},
+    forced_draws::LastChange,
+    notation::{
+        Played,
+        fen::{STARTING_POSITION, write_fen},
+        write_move_list,
+    },
+    rules::Rules,
+};
+
+/// A game and the position it began from, for writing out as PGN.
+pub struct GameRecord<R: Rules> {
+    pub rules: R,
+    pub start: BitBoard,
+    pub moves: Vec<Played>,
+}
+
+impl<R: Rules> GameRecord<R> {
+    pub fn new(rules: R, start: BitBoard) -> Self {
+        Self {
+            rules,
+            start,
+            moves: Vec::new(),
+        }
+    }
+
+    /// Games that do not begin from the usual array, such as odds games, get
+    /// `SetUp` and `FEN` tags for their starting position.
+    pub fn write_pgn(&self, result: Option<GameEnd>) -> String {
+        let mut res = String::new();
+        let result = result.map_or("*", |end| end.pgn_str());
+        let fen = write_fen(&self.start, &LastChange::start());
+
+        writeln!(res, "[Event \"Casual game\"]").unwrap();
+        if self.rules.name() != Variant::Standard.name() {
+            writeln!(res, "[Variant \"{}\"]", self.rules.name()).unwrap();
+        }
+        if fen != STARTING_POSITION {
+            writeln!(res, "[SetUp \"1\"]").unwrap();
+            writeln!(res, "[FEN \"{fen}\"]").unwrap();
+        }
+        writeln!(res, "[Result \"{result}\"]").unwrap();
+        res.push('\n');
+
+        let list = write_move_list(&self.moves);
+        if !list.is_empty() {
+            res.push_str(&list);
+            res.push(' ');
+        }
+        res.push_str(result);
+
+        res
+    }
+}
+
+#[test]
+fn odds_pgn() {
+    use crate::shessboard::{
+        enums::{Color, Piece},
+        notation::Algebraic,
+        odds::Odds,
+        rules::StandardChess,
+    };
+
+    let record = GameRecord::new(StandardChess, BitBoard::new());
+    let pgn = record.write_pgn(None);
+    assert!(!pgn.contains("SetUp"));
+    assert!(pgn.ends_with("\n\n*"));
+
+    let mut start = BitBoard::new();
+    start.give_odds(&Odds::Knight, Piece::King).unwrap();
+    let e4 = Played {
+        turn: 1,
+        color: Color::White,
+        notation: (Algebraic::read("e4").unwrap().0, ""),
+    };
+
+    let mut record = GameRecord::new(Variant::Standard, start);
+    record.moves.push(e4);
+    let pgn = record.write_pgn(Some(GameEnd::BlackWins));
+    assert!(pgn.contains("[SetUp \"1\"]\n"));
+    assert!(pgn.contains("[FEN \"rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/R1BQKBNR w KQkq - 0 1\"]"));
+    assert!(pgn.ends_with("\n\n1. e4 0-1"));
+}
@@ -0,0 +1,139 @@
+use crate::shessboard::{
+    BitBoard,
+    castling::CastlingRights,
+    enums::{Color, Piece},
+    squares::Square,
+};
+
+/// Material handicaps for the stronger player. Pieces are taken from White,
+/// except in pawn and move, where the stronger player has Black and so the
+/// weaker one also gets the first move.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Odds {
+    PawnAndMove,
+    Knight,
+    Rook,
+    Queen,
+    /// Removes whatever stands on the given squares.
+    Custom(Vec<Square>),
+}
+
+impl Odds {
+    pub fn name(&self) -> String {
+        match self {
+            Odds::PawnAndMove => "pawn".to_string(),
+            Odds::Knight => "knight".to_string(),
+            Odds::Rook => "rook".to_string(),
+            Odds::Queen => "queen".to_string(),
+            Odds::Custom(squares) => squares
+                .iter()
+                .map(|sq| sq.to_string())
+                .collect::<Vec<_>>()
+                .join(","),
+        }
+    }
+
+    /// Reads `pawn`, `knight`, `rook`, `queen`, or squares like `a1,b1`.
+    pub fn read(s: &str) -> Option<(Self, &str)> {
+        for odds in [Odds::PawnAndMove, Odds::Knight, Odds::Rook, Odds::Queen] {
+            if let Some(s) = s.strip_prefix(odds.name().as_str()) {
+                return Some((odds, s));
+            }
+        }
+
+        let (sq, mut s) = Square::read(s)?;
+        let mut squares = vec![sq];
+        while let Some((sq, rest)) = s.strip_prefix(',').and_then(Square::read) {
+            squares.push(sq);
+            s = rest;
+        }
+        Some((Odds::Custom(squares), s))
+    }
+
+    pub fn squares(&self) -> Vec<Square> {
+        match self {
+            Odds::PawnAndMove => vec![Square::f7],
+            Odds::Knight => vec![Square::b1],
+            Odds::Rook => vec![Square::a1],
+            Odds::Queen => vec![Square::d1],
+            Odds::Custom(squares) => squares.clone(),
+        }
+    }
+}
+
+impl BitBoard {
+    /// Takes the handicapped pieces off the board and drops the castling
+    /// rights that no longer have a rook. `royal` pieces cannot be given as
+    /// odds.
+    pub fn give_odds(&mut self, odds: &Odds, royal: Piece) -> Result<(), &'static str> {
+        let squares = odds.squares();
+        let royals = self.white.piece_mask(royal) | self.black.piece_mask(royal);
+        if squares.iter().any(|&sq| royals.contains(sq)) {
+            return Err("Royal pieces cannot be given as odds");
+        }
+
+        for sq in squares {
+            self.set_piece(None, sq);
+        }
+        self.derive_castling_rights(royal);
+
+        Ok(())
+    }
+
+    /// Keeps only the castling rights whose `royal` piece and rook are still
+    /// on their starting squares.
+    pub fn derive_castling_rights(&mut self, royal: Piece) {
+        let details = self.metadata.castling_details;
+
+        for color in [Color::White, Color::Black] {
+            let rank = color.starting_rank();
+            let half = self.color(color);
+            let king = half.piece_at(details.oo.king_move.as_move(rank).from) == Some(royal);
+            let rook = |from| half.piece_at(from) == Some(Piece::Rook);
+
+            let derived = CastlingRights {
+                ooo: king && rook(details.ooo.rook_move.as_move(rank).from),
+                oo: king && rook(details.oo.rook_move.as_move(rank).from),
+            };
+            self.metadata.castling_rights_mut(color).0.update(derived);
+        }
+    }
+}
+
+#[test]
+fn odds_setups() {
+    use crate::shessboard::{rules::Rules, variants::Crazyhouse};
+
+    let mut board = BitBoard::new();
+    board.give_odds(&Odds::Rook, Piece::King).unwrap();
+    assert!(!board.white.rooks.as_mask().contains(Square::a1));
+    assert_eq!(
+        board.metadata.white_castling,
+        CastlingRights {
+            ooo: false,
+            oo: true
+        }
+    );
+    assert_eq!(board.metadata.black_castling, CastlingRights::new());
+
+    let mut board = BitBoard::new();
+    board.give_odds(&Odds::PawnAndMove, Piece::King).unwrap();
+    assert_eq!(board.black.pawns.as_mask().occupied(), 7);
+    assert_eq!(board.metadata.to_move, Color::White);
+
+    let mut board = Crazyhouse.start();
+    let (odds, "") = Odds::read("b1,g1,h8").unwrap() else {
+        panic!()
+    };
+    board.give_odds(&odds, Crazyhouse.royal()).unwrap();
+    assert_eq!(board.white.knights.as_mask().occupied(), 0);
+    assert!(board.metadata.white_castling.oo);
+    assert!(!board.metadata.black_castling.oo);
+
+    assert_eq!(Odds::read("queen"), Some((Odds::Queen, "")));
+    assert!(
+        BitBoard::new()
+            .give_odds(&Odds::Custom(vec![Square::e8]), Piece::King)
+            .is_err()
+    );
+}
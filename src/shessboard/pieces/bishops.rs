@@ -60,6 +60,7 @@ impl Bishops {
             .overlay(this.directional_threats(&Queens::SOUTHWEST, false, blockers))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn enumerate_legal_moves(
         &self,
         color: Color,
@@ -67,10 +68,10 @@ impl Bishops {
         passive: &HalfBitBoard,
         passive_mask: Mask,
         kings: Kings,
+        prev_epc: Option<Square>,
         res: &mut Vec<Move>,
     ) {
         let color_and_piece = ColorPiece::new(color, Piece::Bishop);
-        let prev_epc = self
 
         if !self.as_mask().any() {
             return;
@@ -97,6 +98,7 @@ impl Bishops {
                     from_to,
                     capture,
                     castling: None,
+                    prev_epc,
                     promotion: None,
                 });
             }
@@ -38,6 +38,12 @@ impl Kings {
         }
     }
 
+    pub const HILL: Mask = Mask::nil()
+        .set(Square::d4)
+        .set(Square::e4)
+        .set(Square::d5)
+        .set(Square::e5);
+
     const MOVES: BoardMap<Mask> = Self::build_move_db();
 
     const fn build_move_db() -> BoardMap<Mask> {
@@ -78,6 +84,7 @@ impl Kings {
         Self::MOVES.overlays(self.as_mask())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn enumerate_legal_moves(
         &self,
         color: Color,
@@ -86,6 +93,7 @@ impl Kings {
         passive: &HalfBitBoard,
         castling: CastlingRights,
         castling_details: CastlingDetails,
+        prev_epc: Option<Square>,
         res: &mut Vec<Move>,
     ) {
         let color_and_piece = ColorPiece::new(color, Piece::King);
@@ -109,11 +117,40 @@ impl Kings {
                     from_to,
                     capture,
                     castling: None,
+                    prev_epc,
                     promotion: None,
                 });
             }
         }
 
+        self.enumerate_castling_moves(
+            Piece::King,
+            color,
+            active_mask,
+            passive_mask,
+            passive,
+            castling,
+            castling_details,
+            prev_epc,
+            res,
+        );
+    }
+
+    /// Castling moves for the royal `piece` standing on this mask.
+    #[allow(clippy::too_many_arguments)]
+    pub fn enumerate_castling_moves(
+        &self,
+        piece: Piece,
+        color: Color,
+        active_mask: Mask,
+        passive_mask: Mask,
+        passive: &HalfBitBoard,
+        castling: CastlingRights,
+        castling_details: CastlingDetails,
+        prev_epc: Option<Square>,
+        res: &mut Vec<Move>,
+    ) {
+        let color_and_piece = ColorPiece::new(color, piece);
         let unking = (passive_mask | active_mask) & !self.as_mask();
 
         let threats = if castling.ooo || castling.oo {
@@ -125,11 +162,12 @@ impl Kings {
         if castling.ooo {
             Self::castling_move(
                 self.as_mask(),
-                color,
+                color_and_piece,
                 castling_details.ooo,
                 CastlingSide::OOO,
                 threats,
                 unking,
+                prev_epc,
                 res,
             )
         }
@@ -137,38 +175,73 @@ impl Kings {
         if castling.oo {
             Self::castling_move(
                 self.as_mask(),
-                color,
+                color_and_piece,
                 castling_details.oo,
                 CastlingSide::OO,
                 threats,
                 unking,
+                prev_epc,
                 res,
             )
         }
     }
 
+    /// Moves for kings that are not royal, ignoring attacks and castling.
+    pub fn enumerate_commoner_moves(
+        &self,
+        color: Color,
+        active_mask: Mask,
+        passive: &HalfBitBoard,
+        prev_epc: Option<Square>,
+        res: &mut Vec<Move>,
+    ) {
+        let color_and_piece = ColorPiece::new(color, Piece::King);
+
+        for from in self.as_mask() {
+            let possible = Kings::MOVES.at(from) & !active_mask;
+
+            for to in possible {
+                let from_to = ProtoMove { from, to };
+
+                let capture = passive.piece_at(to).map(|p| (to, p));
+
+                res.push(Move {
+                    color_and_piece,
+                    from_to,
+                    capture,
+                    castling: None,
+                    prev_epc,
+                    promotion: None,
+                });
+            }
+        }
+    }
+
     // TODO: fix
+    #[allow(clippy::too_many_arguments)]
     fn castling_move(
         mut king: Mask,
-        color: Color,
+        color_and_piece: ColorPiece,
         detail: CastlingDetail,
         castling: CastlingSide,
         threats: Mask,
         unking: Mask,
+        prev_epc: Option<Square>,
         res: &mut Vec<Move>,
     ) {
-        let rank = color.starting_rank();
+        let rank = color_and_piece.color().starting_rank();
         king |= Mask::new_rank(rank, detail.king_mask);
         let rook = Mask::new_rank(rank, detail.rook_mask);
         if !(king & threats).any() && !(king & unking).any() && !(rook & unking).any() {
             res.push(Move {
-                color_and_piece: ColorPiece::new(color, Piece::King),
+                color_and_piece,
                 from_to: ProtoMove {
                     from: detail.king_move.as_move(rank).from,
                     to: detail.rook_move.as_move(rank).from,
                 },
                 castling: Some(castling),
                 capture: None,
+                prev_epc,
                 promotion: None,
             })
         }
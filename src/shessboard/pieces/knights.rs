@@ -1,5 +1,3 @@
-use std::u64;
-
 use crate::shessboard::{
     boardmap::BoardMap,
     enums::{Color, ColorPiece, Dir, File, Piece, Rank},
@@ -93,6 +91,7 @@ impl Knights {
         active_mask: Mask,
         passive: &HalfBitBoard,
         kings: Kings,
+        prev_epc: Option<Square>,
         res: &mut Vec<Move>,
     ) {
         let color_and_piece = ColorPiece::new(color, Piece::Knight);
@@ -118,6 +117,7 @@ impl Knights {
                     from_to,
                     capture,
                     castling: None,
+                    prev_epc,
                     promotion: None,
                 });
             }
@@ -100,6 +100,31 @@ impl Pawns {
         threat_masks.overlays(self.as_mask())
     }
 
+    pub const PROMOTIONS: &[Piece] = &[Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight];
+    pub const ANTICHESS_PROMOTIONS: &[Piece] = &[
+        Piece::Queen,
+        Piece::Rook,
+        Piece::Bishop,
+        Piece::Knight,
+        Piece::King,
+    ];
+    /// Knightmate pawns may become commoners but never the royal knight.
+    pub const KNIGHTMATE_PROMOTIONS: &[Piece] =
+        &[Piece::Queen, Piece::Rook, Piece::Bishop, Piece::King];
+
+    /// White's pawns in the horde starting position.
+    pub const HORDE: Mask = Mask::visboard([
+        0b_00000000,
+        0b_00000000,
+        0b_00000000,
+        0b_01100110,
+        0b_11111111, //4
+        0b_11111111,
+        0b_11111111,
+        0b_11111111,
+        // abcdefgh
+    ]);
+
     pub const fn promotion_rank(color: Color) -> Rank {
         match color {
             Color::White => Rank::_8,
@@ -107,6 +132,7 @@ impl Pawns {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn enumerate_legal_moves(
         &self,
         color: Color,
@@ -115,9 +141,11 @@ impl Pawns {
         passive: &HalfBitBoard,
         en_passant: Option<EnPassant>,
         kings: Kings,
+        promotions_to: &[Piece],
         res: &mut Vec<Move>,
     ) {
         let color_and_piece = ColorPiece::new(color, Piece::Pawn);
+        let prev_epc = en_passant.map(|ep| ep.to);
 
         if !self.as_mask().any() {
             return;
@@ -145,11 +173,13 @@ impl Pawns {
 
                 promotions(
                     res,
+                    promotions_to,
                     Move {
                         color_and_piece,
                         from_to,
                         castling: None,
                         capture: None,
+                        prev_epc,
                         promotion: None,
                     },
                 );
@@ -171,22 +201,24 @@ impl Pawns {
 
                 promotions(
                     res,
+                    promotions_to,
                     Move {
                         color_and_piece,
                         from_to,
                         castling: None,
                         capture,
+                        prev_epc,
                         promotion: None,
                     },
                 );
             }
 
-            if let Some(EnPassant { to, capture }) = en_passant {
+            if let Some(ep) = en_passant {
                 'out: {
-                    if THREATS.at(from).contains(to) {
-                        let from_to = ProtoMove { from, to };
+                    if THREATS.at(from).contains(ep.to) {
+                        let from_to = ProtoMove { from, to: ep.to };
 
-                        let capture = Some((capture, Piece::Pawn));
+                        let capture = Some((ep.capture(), Piece::Pawn));
 
                         if from_to.makes_king_checked(
                             active_mask,
@@ -203,6 +235,7 @@ impl Pawns {
                             from_to,
                             castling: None,
                             capture,
+                            prev_epc,
                             promotion: None,
                         })
                     }
@@ -210,9 +243,9 @@ impl Pawns {
             }
         }
 
-        fn promotions(res: &mut Vec<Move>, mut mv: Move) {
+        fn promotions(res: &mut Vec<Move>, promotions_to: &[Piece], mut mv: Move) {
             if mv.from_to.to.rank() == Pawns::promotion_rank(mv.color_and_piece.color()) {
-                for piece in [Piece::Queen, Piece::Rook, Piece::Bishop, Piece::Knight] {
+                for &piece in promotions_to {
                     mv.promotion = Some(piece);
                     res.push(mv);
                 }
@@ -221,16 +254,54 @@ impl Pawns {
             }
         }
     }
+
+    /// Double steps for pawns on their own first rank, as allowed in horde.
+    /// These never leave an en passant square behind, and the moving side is
+    /// assumed to have no king to expose.
+    pub fn enumerate_first_rank_double_steps(
+        &self,
+        color: Color,
+        occupied: Mask,
+        prev_epc: Option<Square>,
+        res: &mut Vec<Move>,
+    ) {
+        let color_and_piece = ColorPiece::new(color, Piece::Pawn);
+        let dir = match color {
+            Color::White => Dir::North,
+            Color::Black => Dir::South,
+        };
+
+        for from in self.as_mask() & color.starting_rank().as_mask() {
+            let (Some(step), Some(to)) = (from.go(dir), from.goes([dir, dir])) else {
+                continue;
+            };
+            if occupied.contains(step) || occupied.contains(to) {
+                continue;
+            }
+
+            res.push(Move {
+                color_and_piece,
+                from_to: ProtoMove { from, to },
+                castling: None,
+                capture: None,
+                prev_epc,
+                promotion: None,
+            });
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EnPassant {
     pub to: Square,
-
 }
 
 impl EnPassant {
-    pub fn capture(self) -> Square {
-        if 
+    pub const fn capture(self) -> Square {
+        if let Rank::_3 = self.to.rank() {
+            self.to.go(Dir::North).unwrap()
+        } else {
+            self.to.go(Dir::South).unwrap()
+        }
     }
 }
@@ -113,6 +113,7 @@ impl Queens {
         res
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn enumerate_legal_moves(
         &self,
         color: Color,
@@ -120,6 +121,7 @@ impl Queens {
         passive: &HalfBitBoard,
         passive_mask: Mask,
         kings: Kings,
+        prev_epc: Option<Square>,
         res: &mut Vec<Move>,
     ) {
         let color_and_piece = ColorPiece::new(color, Piece::Queen);
@@ -153,6 +155,7 @@ impl Queens {
                     from_to,
                     capture,
                     castling: None,
+                    prev_epc,
                     promotion: None,
                 });
             }
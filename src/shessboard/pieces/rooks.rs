@@ -55,6 +55,7 @@ impl Rooks {
             .overlay(this.directional_threats(&Queens::WEST, false, blockers))
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn enumerate_legal_moves(
         &self,
         color: Color,
@@ -62,6 +63,7 @@ impl Rooks {
         passive: &HalfBitBoard,
         passive_mask: Mask,
         kings: Kings,
+        prev_epc: Option<Square>,
         res: &mut Vec<Move>,
     ) {
         let color_and_piece = ColorPiece::new(color, Piece::Rook);
@@ -90,6 +92,7 @@ impl Rooks {
                     from_to,
                     capture,
                     castling: None,
+                    prev_epc,
                     promotion: None,
                 });
             }
@@ -0,0 +1,81 @@
+use crate::shessboard::enums::{Color, ColorPiece, Piece};
+
+/// Captured pieces held in hand in crazyhouse, ready to be dropped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Hash)]
+pub struct Pocket {
+    counts: [u8; 5],
+}
+
+impl Pocket {
+    /// Droppable pieces in the order they are listed in FEN.
+    pub const PIECES: [Piece; 5] = [
+        Piece::Queen,
+        Piece::Rook,
+        Piece::Bishop,
+        Piece::Knight,
+        Piece::Pawn,
+    ];
+
+    pub const fn new() -> Self {
+        Self { counts: [0; 5] }
+    }
+
+    const fn index(piece: Piece) -> usize {
+        match piece {
+            Piece::King => panic!("kings cannot be pocketed"),
+            p => p as usize - 1,
+        }
+    }
+
+    pub const fn count(&self, piece: Piece) -> u8 {
+        self.counts[Self::index(piece)]
+    }
+
+    pub fn add(&mut self, piece: Piece) {
+        self.counts[Self::index(piece)] += 1;
+    }
+
+    pub fn remove(&mut self, piece: Piece) {
+        self.counts[Self::index(piece)] -= 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts == [0; 5]
+    }
+
+    pub fn pieces(&self) -> impl Iterator<Item = Piece> + '_ {
+        Self::PIECES.into_iter().filter(|&p| self.count(p) > 0)
+    }
+
+    /// Writes the pocket as FEN piece letters, e.g. `QNN`.
+    pub fn letters(&self, color: Color) -> String {
+        let mut res = String::new();
+        for piece in Self::PIECES {
+            for _ in 0..self.count(piece) {
+                res.push(ColorPiece::new(color, piece).letter());
+            }
+        }
+        res
+    }
+}
+
+#[test]
+fn pocket_counts() {
+    let mut pocket = Pocket::new();
+    assert!(pocket.is_empty());
+
+    pocket.add(Piece::Knight);
+    pocket.add(Piece::Pawn);
+    pocket.add(Piece::Knight);
+    pocket.add(Piece::Queen);
+    assert_eq!(pocket.count(Piece::Knight), 2);
+    assert_eq!(pocket.letters(Color::White), "QNNP");
+    assert_eq!(pocket.letters(Color::Black), "qnnp");
+    assert_eq!(
+        pocket.pieces().collect::<Vec<_>>(),
+        vec![Piece::Queen, Piece::Knight, Piece::Pawn]
+    );
+
+    pocket.remove(Piece::Queen);
+    assert_eq!(pocket.count(Piece::Queen), 0);
+}
@@ -0,0 +1,155 @@
+use std::ops::RangeInclusive;
+
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::shessboard::{
+    BitBoard,
+    boardmap::BoardMap,
+    enums::{Color, ColorPiece, Piece, Rank},
+    masks::Mask,
+    metadata::Metadata,
+    squares::Square,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PositionConstraints {
+    pub white: Vec<Piece>,
+    pub black: Vec<Piece>,
+    pub to_move: Option<Color>,
+    pub allow_check: bool,
+    /// Ranks pawns may stand on, as seen from their own side of the board.
+    pub pawn_ranks: RangeInclusive<Rank>,
+}
+
+impl PositionConstraints {
+    pub const ATTEMPTS: usize = 10_000;
+
+    pub fn new(white: Vec<Piece>, black: Vec<Piece>) -> Self {
+        Self {
+            white,
+            black,
+            to_move: None,
+            allow_check: false,
+            pawn_ranks: Rank::_2..=Rank::_7,
+        }
+    }
+
+    /// Reads a material set such as `KRP vs KR`, white first.
+    pub fn read(s: &str) -> Option<(Self, &str)> {
+        let (white, s) = read_side(s)?;
+        let s = s.trim_start().strip_prefix("vs")?.trim_start();
+        let (black, s) = read_side(s)?;
+        return Some((Self::new(white, black), s));
+
+        fn read_side(mut s: &str) -> Option<(Vec<Piece>, &str)> {
+            let mut res = vec![];
+            while let Some((p, ss)) = Piece::read(s, false) {
+                res.push(p);
+                s = ss;
+            }
+            if res.iter().filter(|p| **p == Piece::King).count() == 1 {
+                Some((res, s))
+            } else {
+                None
+            }
+        }
+    }
+
+    fn pawn_mask(&self, color: Color) -> Mask {
+        let mut res = Mask::nil();
+        for rank in 0..8 {
+            let rank = Rank::rank(rank).unwrap();
+            if self.pawn_ranks.contains(&rank) {
+                res |= rank.as_mask();
+            }
+        }
+        match color {
+            Color::White => res,
+            Color::Black => res.mirror(),
+        }
+    }
+}
+
+impl BitBoard {
+    /// Places the constrained material on random squares until the result is
+    /// a legal position with at least one legal move, giving up after
+    /// [`PositionConstraints::ATTEMPTS`] tries.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R, constraints: &PositionConstraints) -> Option<Self> {
+        let mut moves = Vec::with_capacity(50);
+
+        'attempt: for _ in 0..PositionConstraints::ATTEMPTS {
+            let mut metadata = Metadata::empty();
+            metadata.to_move = constraints.to_move.unwrap_or(if rng.random() {
+                Color::White
+            } else {
+                Color::Black
+            });
+            let mut board = BitBoard::new_board(&BoardMap::new_with(None), metadata);
+
+            for (color, pieces) in [
+                (Color::White, &constraints.white),
+                (Color::Black, &constraints.black),
+            ] {
+                for &piece in pieces {
+                    let mut allowed = !board.as_mask();
+                    match piece {
+                        Piece::Pawn => allowed &= constraints.pawn_mask(color),
+                        Piece::King => allowed &= !board.color(color.other()).kings.threats(),
+                        _ => {}
+                    }
+
+                    let squares = allowed.iter().collect::<Vec<Square>>();
+                    let Some(&sq) = squares.choose(rng) else {
+                        continue 'attempt;
+                    };
+                    board.set_piece(Some(ColorPiece::new(color, piece)), sq);
+                }
+            }
+
+            let to_move = board.metadata.to_move;
+            if board.is_in_check(to_move.other())
+                || (!constraints.allow_check && board.is_in_check(to_move))
+            {
+                continue;
+            }
+
+            moves.clear();
+            board.generate_moves(&mut moves);
+            if moves.is_empty() {
+                continue;
+            }
+
+            return Some(board);
+        }
+
+        None
+    }
+}
+
+#[test]
+fn random_endgame_positions() {
+    use rand::{SeedableRng, rngs::StdRng};
+
+    let mut rng = StdRng::from_seed(*b"3.141592653589793238462643383279");
+    let (mut constraints, "") = PositionConstraints::read("KRP vs KR").unwrap() else {
+        panic!()
+    };
+    constraints.to_move = Some(Color::Black);
+
+    for _ in 0..100 {
+        let board = BitBoard::random(&mut rng, &constraints).unwrap();
+
+        assert_eq!(board.metadata.to_move, Color::Black);
+        assert_eq!(board.white.kings.as_mask().occupied(), 1);
+        assert_eq!(board.white.rooks.as_mask().occupied(), 1);
+        assert_eq!(board.white.pawns.as_mask().occupied(), 1);
+        assert_eq!(board.black.kings.as_mask().occupied(), 1);
+        assert_eq!(board.black.rooks.as_mask().occupied(), 1);
+        assert_eq!(board.as_mask().occupied(), 5);
+        assert!(!(board.white.pawns.as_mask() & (Rank::_1.as_mask() | Rank::_8.as_mask())).any());
+        assert!(!board.is_in_check(Color::White));
+        assert!(!board.is_in_check(Color::Black));
+    }
+
+    assert_eq!(PositionConstraints::read("KQ vs Q"), None);
+}
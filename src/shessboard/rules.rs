@@ -0,0 +1,445 @@
+use crate::shessboard::{
+    BitBoard, Undo,
+    enums::{Color, GameEnd, Piece, Variant},
+    forced_draws::{LastChange, ThreefoldRule},
+    half::HalfBitBoard,
+    masks::Mask,
+    moves::Move,
+    notation::Algebraic,
+    pieces::Millipawns,
+    variants::{
+        Antichess, Atomic, Bughouse, Crazyhouse, Extinction, FogOfWar, Horde, KingOfTheHill,
+        Knightmate, Marseillais, RacingKings, ThreeCheck,
+    },
+    zobrist::{BitBoardHasher, HashResult},
+};
+
+/// A rule set that the game loop, the game record and the engine can play
+/// by. Every hook defaults to plain chess as `BitBoard` plays it, so a
+/// variant only overrides what it changes.
+pub trait Rules {
+    /// Name used in the PGN `Variant` tag and to pick the rules by hand.
+    fn name(&self) -> &'static str;
+
+    fn read(s: &str) -> Option<(Self, &str)>
+    where
+        Self: Sized;
+
+    fn start(&self) -> BitBoard;
+
+    /// The piece that may not be left attacked.
+    fn royal(&self) -> Piece {
+        Piece::King
+    }
+
+    /// Moves of the `piece`s on `from`, which the move generators below
+    /// are built from.
+    fn generate_piece_moves(
+        &self,
+        board: &BitBoard,
+        piece: Piece,
+        from: Mask,
+        res: &mut Vec<Move>,
+    ) {
+        board.generate_piece_moves(piece, from, res);
+    }
+
+    fn generate_moves(&self, board: &BitBoard, res: &mut Vec<Move>) {
+        board.generate_moves_by(res, |piece, from, res| {
+            self.generate_piece_moves(board, piece, from, res)
+        });
+    }
+
+    /// Captures and queen promotions, a subset of `generate_moves`.
+    fn generate_captures(&self, board: &BitBoard, res: &mut Vec<Move>) {
+        board.generate_captures_by(res, |piece, from, res| {
+            self.generate_piece_moves(board, piece, from, res)
+        });
+    }
+
+    fn apply(&self, board: &mut BitBoard, mv: Move) -> Undo {
+        board.apply(mv)
+    }
+
+    fn undo(&self, board: &mut BitBoard, mv: Move, undo: &Undo) {
+        board.undo(mv, undo);
+    }
+
+    fn has_legal_move(&self, board: &BitBoard) -> bool {
+        board.has_legal_move_by(|piece, from, res| {
+            self.generate_piece_moves(board, piece, from, res)
+        })
+    }
+
+    fn is_in_check(&self, board: &BitBoard, color: Color) -> bool {
+        board.royal_attacked(color, self.royal())
+    }
+
+    fn gives_check(&self, board: &BitBoard, mv: Move) -> bool {
+        board.checks_royal(mv, self.royal())
+    }
+
+    /// Whether either side can still win, short of the end conditions
+    /// checked in `game_end`.
+    fn sufficient_material(&self, board: &BitBoard) -> bool {
+        board.sufficient_checkmating_materiel()
+    }
+
+    /// Whether what a capture wins in material is a fair guide to it, so a
+    /// search may prune and order captures by their exchanges.
+    fn material_exchanges_sound(&self) -> bool {
+        true
+    }
+
+    /// Whether passing is a fair stand-in for the worst move, so a search
+    /// may try null moves.
+    fn null_move_sound(&self) -> bool {
+        true
+    }
+
+    /// What `half`'s material is worth to an evaluation.
+    fn materiel(&self, half: &HalfBitBoard) -> Millipawns {
+        half.materiel()
+    }
+
+    /// What an evaluation adds for `color` heading for goals of the rules'
+    /// own, on top of material and piece placement.
+    fn goal_incentive(&self, board: &BitBoard, color: Color) -> Millipawns {
+        0
+    }
+
+    /// How the game ended, given the legal moves in `board`. Only whether
+    /// there are any may count, so a search can pass just the captures when
+    /// there are some.
+    fn game_end<'a>(
+        &self,
+        board: &BitBoard,
+        moves: &[Move],
+        hash: HashResult,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Option<GameEnd> {
+        GameEnd::determine(self, board, moves, hash, change, three)
+    }
+
+    /// Variant state beyond the pieces, castling and en passant that tells
+    /// positions apart. Rules that use it must also override `hash_delta`.
+    fn extra_hash(&self, hasher: &BitBoardHasher, board: &BitBoard) -> HashResult {
+        0
+    }
+
+    fn hash(&self, hasher: &BitBoardHasher, board: &BitBoard) -> HashResult {
+        hasher.hash_full(board) ^ self.extra_hash(hasher, board)
+    }
+
+    fn hash_delta(
+        &self,
+        hasher: &BitBoardHasher,
+        board: &BitBoard,
+        hash: HashResult,
+        mv: Move,
+    ) -> HashResult {
+        hasher.delta(board, hash, mv)
+    }
+
+    fn notation(&self, mv: &Move, legal_moves: &[Move]) -> Algebraic {
+        Algebraic::new(mv, legal_moves)
+    }
+
+    /// Suffix written after `mv` in `board`. Only a check needs the move
+    /// played, and then only until a first reply turns up.
+    fn suffix(&self, board: &BitBoard, mv: Move) -> &'static str {
+        if !self.gives_check(board, mv) {
+            return "";
+        }
+
+        let mut after = board.clone();
+        self.apply(&mut after, mv);
+        if self.has_legal_move(&after) {
+            "+"
+        } else {
+            "#"
+        }
+    }
+
+    fn perft(&self, board: &BitBoard, depth: usize) -> usize {
+        let mut moves = Vec::with_capacity(50);
+        self.generate_moves(board, &mut moves);
+        if depth <= 1 {
+            return moves.len();
+        }
+
+        moves
+            .into_iter()
+            .map(|mv| {
+                let mut board = board.clone();
+                self.apply(&mut board, mv);
+                self.perft(&board, depth - 1)
+            })
+            .sum()
+    }
+}
+
+/// Plain chess with no variant rules.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StandardChess;
+
+impl Rules for StandardChess {
+    fn name(&self) -> &'static str {
+        Variant::Standard.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((StandardChess, s.strip_prefix(Variant::Standard.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        BitBoard::new()
+    }
+}
+
+impl Variant {
+    /// The rules of this variant.
+    pub fn rules(&self) -> &'static dyn Rules {
+        match self {
+            Variant::Standard => &StandardChess,
+            Variant::KingOfTheHill => &KingOfTheHill,
+            Variant::ThreeCheck => &ThreeCheck,
+            Variant::Antichess => &Antichess,
+            Variant::Atomic => &Atomic,
+            Variant::Crazyhouse => &Crazyhouse,
+            Variant::Bughouse => &Bughouse,
+            Variant::Horde => &Horde,
+            Variant::RacingKings => &RacingKings,
+            Variant::FogOfWar => &FogOfWar,
+            Variant::Knightmate => &Knightmate,
+            Variant::Extinction => &Extinction,
+            Variant::Marseillais => &Marseillais,
+        }
+    }
+}
+
+/// Any of the built-in variants, picked at run time.
+impl Rules for Variant {
+    fn name(&self) -> &'static str {
+        Variant::name(self)
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Variant::read(s)
+    }
+
+    fn start(&self) -> BitBoard {
+        self.rules().start()
+    }
+
+    fn royal(&self) -> Piece {
+        self.rules().royal()
+    }
+
+    fn generate_piece_moves(
+        &self,
+        board: &BitBoard,
+        piece: Piece,
+        from: Mask,
+        res: &mut Vec<Move>,
+    ) {
+        self.rules().generate_piece_moves(board, piece, from, res);
+    }
+
+    fn generate_moves(&self, board: &BitBoard, res: &mut Vec<Move>) {
+        self.rules().generate_moves(board, res);
+    }
+
+    fn generate_captures(&self, board: &BitBoard, res: &mut Vec<Move>) {
+        self.rules().generate_captures(board, res);
+    }
+
+    fn apply(&self, board: &mut BitBoard, mv: Move) -> Undo {
+        self.rules().apply(board, mv)
+    }
+
+    fn undo(&self, board: &mut BitBoard, mv: Move, undo: &Undo) {
+        self.rules().undo(board, mv, undo);
+    }
+
+    fn has_legal_move(&self, board: &BitBoard) -> bool {
+        self.rules().has_legal_move(board)
+    }
+
+    fn is_in_check(&self, board: &BitBoard, color: Color) -> bool {
+        self.rules().is_in_check(board, color)
+    }
+
+    fn gives_check(&self, board: &BitBoard, mv: Move) -> bool {
+        self.rules().gives_check(board, mv)
+    }
+
+    fn sufficient_material(&self, board: &BitBoard) -> bool {
+        self.rules().sufficient_material(board)
+    }
+
+    fn material_exchanges_sound(&self) -> bool {
+        self.rules().material_exchanges_sound()
+    }
+
+    fn null_move_sound(&self) -> bool {
+        self.rules().null_move_sound()
+    }
+
+    fn materiel(&self, half: &HalfBitBoard) -> Millipawns {
+        self.rules().materiel(half)
+    }
+
+    fn goal_incentive(&self, board: &BitBoard, color: Color) -> Millipawns {
+        self.rules().goal_incentive(board, color)
+    }
+
+    fn game_end<'a>(
+        &self,
+        board: &BitBoard,
+        moves: &[Move],
+        hash: HashResult,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Option<GameEnd> {
+        self.rules().game_end(board, moves, hash, change, three)
+    }
+
+    fn extra_hash(&self, hasher: &BitBoardHasher, board: &BitBoard) -> HashResult {
+        self.rules().extra_hash(hasher, board)
+    }
+
+    fn hash(&self, hasher: &BitBoardHasher, board: &BitBoard) -> HashResult {
+        self.rules().hash(hasher, board)
+    }
+
+    fn hash_delta(
+        &self,
+        hasher: &BitBoardHasher,
+        board: &BitBoard,
+        hash: HashResult,
+        mv: Move,
+    ) -> HashResult {
+        self.rules().hash_delta(hasher, board, hash, mv)
+    }
+
+    fn notation(&self, mv: &Move, legal_moves: &[Move]) -> Algebraic {
+        self.rules().notation(mv, legal_moves)
+    }
+
+    fn suffix(&self, board: &BitBoard, mv: Move) -> &'static str {
+        self.rules().suffix(board, mv)
+    }
+}
+
+#[test]
+fn standard_rules() {
+    let board = StandardChess.start();
+    assert_eq!(board, Variant::Standard.start());
+    assert_eq!(StandardChess.perft(&board, 3), 8_902);
+    assert_eq!(
+        Variant::Atomic.perft(&Variant::Atomic.start(), 3),
+        Atomic.perft(&Atomic.start(), 3)
+    );
+    assert_eq!(StandardChess::read("standard"), Some((StandardChess, "")));
+}
+
+#[test]
+fn rules_without_forking() {
+    use crate::shessboard::enums::Color;
+
+    /// The first capture wins, on top of the standard rules.
+    struct FirstBlood;
+
+    impl Rules for FirstBlood {
+        fn name(&self) -> &'static str {
+            "firstblood"
+        }
+
+        fn read(s: &str) -> Option<(Self, &str)> {
+            Some((FirstBlood, s.strip_prefix("firstblood")?))
+        }
+
+        fn start(&self) -> BitBoard {
+            BitBoard::new()
+        }
+
+        fn game_end<'a>(
+            &self,
+            board: &BitBoard,
+            moves: &[Move],
+            hash: HashResult,
+            change: &'a LastChange<'a>,
+            three: &'a ThreefoldRule<'a>,
+        ) -> Option<GameEnd> {
+            let color = board.metadata.to_move;
+            if board.color(color).as_mask().occupied() < 16 {
+                Some(GameEnd::from_color(color.other()))
+            } else {
+                GameEnd::determine(self, board, moves, hash, change, three)
+            }
+        }
+    }
+
+    let mut board = FirstBlood.start();
+    let mut moves = vec![];
+    for s in ["e4", "d5", "exd5"] {
+        moves.clear();
+        FirstBlood.generate_moves(&board, &mut moves);
+        let (n, "") = Algebraic::read(s).unwrap() else {
+            panic!()
+        };
+        FirstBlood.apply(&mut board, n.find(&moves)[0]);
+    }
+
+    moves.clear();
+    FirstBlood.generate_moves(&board, &mut moves);
+    assert_eq!(
+        FirstBlood.game_end(
+            &board,
+            &moves,
+            0,
+            &LastChange::start(),
+            &ThreefoldRule::empty()
+        ),
+        Some(GameEnd::from_color(Color::White))
+    );
+}
+
+#[test]
+fn piece_moves_without_forking() {
+    /// Knights stay at home, on top of the standard rules.
+    struct Stables;
+
+    impl Rules for Stables {
+        fn name(&self) -> &'static str {
+            "stables"
+        }
+
+        fn read(s: &str) -> Option<(Self, &str)> {
+            Some((Stables, s.strip_prefix("stables")?))
+        }
+
+        fn start(&self) -> BitBoard {
+            BitBoard::new()
+        }
+
+        fn generate_piece_moves(
+            &self,
+            board: &BitBoard,
+            piece: Piece,
+            from: Mask,
+            res: &mut Vec<Move>,
+        ) {
+            if piece != Piece::Knight {
+                board.generate_piece_moves(piece, from, res);
+            }
+        }
+    }
+
+    let board = Stables.start();
+    assert_eq!(Stables.perft(&board, 1), 16);
+    assert_eq!(Stables.perft(&board, 2), 256);
+    assert!(Stables.has_legal_move(&board));
+}
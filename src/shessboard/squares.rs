@@ -73,6 +73,14 @@ impl Square {
         (f.as_file() - of.as_file()).abs() + (r.as_rank() - or.as_rank()).abs()
     }
 
+    pub const fn mirror(&self) -> Self {
+        Self::new(self.index() ^ 56).unwrap()
+    }
+
+    pub const fn reflect(&self) -> Self {
+        Self::new(self.index() ^ 7).unwrap()
+    }
+
     pub const fn go(&self, dir: Dir) -> Option<Self> {
         let val = self.index();
         let (f, r) = dir.as_offset();
@@ -121,6 +129,9 @@ fn why_u_no_worky() {
     assert_eq!(at(B, _7).index(), 49);
 
     assert_eq!(at(C, _5).algebraic(), (C, _5));
+
+    assert_eq!(at(C, _2).mirror(), at(C, _7));
+    assert_eq!(at(C, _2).reflect(), at(F, _2));
 }
 
 #[test]
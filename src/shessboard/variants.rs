@@ -0,0 +1,1783 @@
+use crate::shessboard::{
+    BitBoard, Undo,
+    boardmap::BoardMap,
+    castling::CastlingRights,
+    enums::{Color, ColorPiece, File, GameEnd, Piece, Rank, Variant},
+    explosions::Explosion,
+    forced_draws::{LastChange, ThreefoldRule},
+    half::HalfBitBoard,
+    masks::Mask,
+    metadata::{Metadata, SubTurn},
+    moves::Move,
+    pieces::{Millipawns, P, kings::Kings, pawns::Pawns},
+    retain_from,
+    rules::{Rules, StandardChess},
+    squares::Square,
+    zobrist::{BitBoardHasher, HashResult},
+};
+
+const NO_CASTLING: CastlingRights = CastlingRights {
+    ooo: false,
+    oo: false,
+};
+
+/// The standard starting position, played as `variant`.
+fn standard_start(variant: Variant) -> BitBoard {
+    let mut board = BitBoard::new();
+    board.metadata.variant = variant;
+    board
+}
+
+/// Moves for rules where nothing is royal: kings step into attacks but
+/// still castle, and no move is kept from exposing anything.
+fn generate_kingless_moves(
+    board: &BitBoard,
+    piece: Piece,
+    from: Mask,
+    promotions: &[Piece],
+    res: &mut Vec<Move>,
+) {
+    if piece == Piece::King {
+        board.generate_commoner_moves(from, res);
+        board.generate_castling_moves(Piece::King, from, res);
+    } else {
+        board.generate_guarded_moves(piece, from, Kings::nil(), promotions, res);
+    }
+}
+
+/// The pocket keys of both sides.
+fn hash_pockets(hasher: &BitBoardHasher, board: &BitBoard) -> HashResult {
+    hasher.white.hash_pocket(&board.metadata.white_pocket)
+        ^ hasher.black.hash_pocket(&board.metadata.black_pocket)
+}
+
+/// How the pocket keys change as `mv` is played. Captures stay out of the
+/// mover's pocket if they go `to_partner`, as in `BitBoard::update_pockets`.
+fn pocket_delta(
+    hasher: &BitBoardHasher,
+    board: &BitBoard,
+    mv: Move,
+    to_partner: bool,
+) -> HashResult {
+    let (color, piece) = mv.color_and_piece.split();
+    let same = hasher.color(color);
+    let pocket = board.metadata.pocket(color);
+    let mut res = 0;
+
+    if mv.is_drop() {
+        let count = pocket.count(piece);
+        res ^= same.hash_pocket_count(piece, count) ^ same.hash_pocket_count(piece, count - 1);
+    }
+
+    if let Some(p) = board.pocketed(mv)
+        && !to_partner
+    {
+        let count = pocket.count(p);
+        res ^= same.hash_pocket_count(p, count) ^ same.hash_pocket_count(p, count + 1);
+    }
+
+    res
+}
+
+/// Pull of the hill on a king in king of the hill.
+const HILL_INCENTIVE: (BoardMap<Millipawns>, BoardMap<Millipawns>) = {
+    BoardMap::board_and_mirror(&[
+        [0; 8],
+        [0, P / 10, P / 10, P / 10, P / 10, P / 10, P / 10, 0],
+        [0, P / 10, P / 2, P / 2, P / 2, P / 2, P / 10, 0],
+        [0, P / 10, P / 2, 2 * P, 2 * P, P / 2, P / 10, 0],
+        [0, P / 10, P / 2, 2 * P, 2 * P, P / 2, P / 10, 0],
+        [0, P / 10, P / 2, P / 2, P / 2, P / 2, P / 10, 0],
+        [0, P / 10, P / 10, P / 10, P / 10, P / 10, P / 10, 0],
+        [0; 8],
+    ])
+};
+
+/// Bringing the king to the centre also wins.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KingOfTheHill;
+
+impl Rules for KingOfTheHill {
+    fn name(&self) -> &'static str {
+        Variant::KingOfTheHill.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((
+            KingOfTheHill,
+            s.strip_prefix(Variant::KingOfTheHill.name())?,
+        ))
+    }
+
+    fn start(&self) -> BitBoard {
+        standard_start(Variant::KingOfTheHill)
+    }
+
+    /// Kings are drawn towards the hill.
+    fn goal_incentive(&self, board: &BitBoard, color: Color) -> Millipawns {
+        let table = match color {
+            Color::White => &HILL_INCENTIVE.0,
+            Color::Black => &HILL_INCENTIVE.1,
+        };
+        table.sum_mask(board.color(color).kings.as_mask())
+    }
+
+    fn game_end<'a>(
+        &self,
+        board: &BitBoard,
+        moves: &[Move],
+        hash: HashResult,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Option<GameEnd> {
+        let last_moved = board.metadata.to_move.other();
+        if board
+            .color(last_moved)
+            .kings
+            .as_mask()
+            .overlap(Kings::HILL)
+            .any()
+        {
+            Some(GameEnd::from_color(last_moved))
+        } else {
+            StandardChess.game_end(board, moves, hash, change, three)
+        }
+    }
+}
+
+#[test]
+fn king_of_the_hill() {
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::KingOfTheHill;
+    board.set_piece(Some(ColorPiece::WhiteKing), Square::e3);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::a8);
+    board.set_piece(Some(ColorPiece::BlackRook), Square::h8);
+
+    let mut moves = vec![];
+    KingOfTheHill.generate_moves(&board, &mut moves);
+    let three = ThreefoldRule::empty();
+    let change = LastChange::start();
+    assert_eq!(
+        KingOfTheHill.game_end(&board, &moves, 0, &change, &three),
+        None
+    );
+
+    let mv = *moves
+        .iter()
+        .find(|m| m.from_to == Square::e3.to(Square::e4))
+        .unwrap();
+    KingOfTheHill.apply(&mut board, mv);
+    moves.clear();
+    KingOfTheHill.generate_moves(&board, &mut moves);
+    assert_eq!(
+        KingOfTheHill.game_end(&board, &moves, 0, &change, &three),
+        Some(GameEnd::WhiteWins)
+    );
+
+    assert_eq!(
+        StandardChess.game_end(&board, &moves, 0, &change, &three),
+        None
+    );
+}
+
+/// Giving a third check also wins.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ThreeCheck;
+
+impl Rules for ThreeCheck {
+    fn name(&self) -> &'static str {
+        Variant::ThreeCheck.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((ThreeCheck, s.strip_prefix(Variant::ThreeCheck.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        standard_start(Variant::ThreeCheck)
+    }
+
+    fn apply(&self, board: &mut BitBoard, mv: Move) -> Undo {
+        let color = mv.color_and_piece.color();
+        let undo = board.apply(mv);
+        if board.is_in_check(color.other()) {
+            *board.metadata.checks_mut(color) += 1;
+        }
+        undo
+    }
+
+    fn undo(&self, board: &mut BitBoard, mv: Move, undo: &Undo) {
+        let color = mv.color_and_piece.color();
+        if board.is_in_check(color.other()) {
+            *board.metadata.checks_mut(color) -= 1;
+        }
+        board.undo(mv, undo);
+    }
+
+    fn extra_hash(&self, hasher: &BitBoardHasher, board: &BitBoard) -> HashResult {
+        hasher.white.hash_checks(board.metadata.white_checks)
+            ^ hasher.black.hash_checks(board.metadata.black_checks)
+    }
+
+    fn hash_delta(
+        &self,
+        hasher: &BitBoardHasher,
+        board: &BitBoard,
+        hash: HashResult,
+        mv: Move,
+    ) -> HashResult {
+        let hash = hasher.delta(board, hash, mv);
+        if !self.gives_check(board, mv) {
+            return hash;
+        }
+
+        let color = mv.color_and_piece.color();
+        let same = hasher.color(color);
+        let count = board.metadata.checks(color);
+        hash ^ same.hash_checks(count) ^ same.hash_checks(count + 1)
+    }
+
+    fn game_end<'a>(
+        &self,
+        board: &BitBoard,
+        moves: &[Move],
+        hash: HashResult,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Option<GameEnd> {
+        let last_moved = board.metadata.to_move.other();
+        if board.metadata.checks(last_moved) >= 3 {
+            Some(GameEnd::from_color(last_moved))
+        } else {
+            StandardChess.game_end(board, moves, hash, change, three)
+        }
+    }
+}
+
+#[test]
+fn three_check() {
+    use crate::shessboard::notation::fen::read_fen;
+
+    let ((mut board, change), "") = read_fen(
+        Variant::ThreeCheck,
+        "rnbqkbnr/ppp2ppp/8/3pp3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 0 3 +2+0",
+    )
+    .unwrap() else {
+        panic!()
+    };
+    let three = ThreefoldRule::empty();
+    let mut moves = vec![];
+    ThreeCheck.generate_moves(&board, &mut moves);
+    assert_eq!(
+        ThreeCheck.game_end(&board, &moves, 0, &change, &three),
+        None
+    );
+
+    let mv = *moves
+        .iter()
+        .find(|m| m.from_to == Square::f1.to(Square::b5))
+        .unwrap();
+    ThreeCheck.apply(&mut board, mv);
+    moves.clear();
+    ThreeCheck.generate_moves(&board, &mut moves);
+    assert_eq!(board.metadata.white_checks, 3);
+    assert_eq!(
+        ThreeCheck.game_end(&board, &moves, 0, &change, &three),
+        Some(GameEnd::WhiteWins)
+    );
+}
+
+/// Captures are forced, and the side left without moves wins.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Antichess;
+
+impl Rules for Antichess {
+    fn name(&self) -> &'static str {
+        Variant::Antichess.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((Antichess, s.strip_prefix(Variant::Antichess.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        let mut board = standard_start(Variant::Antichess);
+        board.metadata.white_castling = NO_CASTLING;
+        board.metadata.black_castling = NO_CASTLING;
+        board
+    }
+
+    /// Kings are commoners that never castle, and pawns may promote to them.
+    fn generate_piece_moves(
+        &self,
+        board: &BitBoard,
+        piece: Piece,
+        from: Mask,
+        res: &mut Vec<Move>,
+    ) {
+        if piece == Piece::King {
+            board.generate_commoner_moves(from, res);
+        } else {
+            board.generate_guarded_moves(
+                piece,
+                from,
+                Kings::nil(),
+                Pawns::ANTICHESS_PROMOTIONS,
+                res,
+            );
+        }
+    }
+
+    fn generate_moves(&self, board: &BitBoard, res: &mut Vec<Move>) {
+        let start = res.len();
+        board.generate_moves_by(res, |piece, from, res| {
+            self.generate_piece_moves(board, piece, from, res)
+        });
+        if res[start..].iter().any(|mv| mv.capture.is_some()) {
+            retain_from(res, start, |mv| mv.capture.is_some());
+        }
+    }
+
+    fn generate_captures(&self, board: &BitBoard, res: &mut Vec<Move>) {
+        let start = res.len();
+        board.generate_captures_by(res, |piece, from, res| {
+            self.generate_piece_moves(board, piece, from, res)
+        });
+        if res[start..].iter().any(|mv| mv.capture.is_some()) {
+            retain_from(res, start, |mv| mv.capture.is_some());
+        }
+    }
+
+    fn gives_check(&self, board: &BitBoard, mv: Move) -> bool {
+        false
+    }
+
+    fn sufficient_material(&self, board: &BitBoard) -> bool {
+        board.white.has_sufficient_antichess_materiel(&board.black)
+    }
+
+    /// Giving material away is the point.
+    fn material_exchanges_sound(&self) -> bool {
+        false
+    }
+
+    /// Being forced to move, and to capture, decides most games.
+    fn null_move_sound(&self) -> bool {
+        false
+    }
+
+    /// Every piece left counts against its owner.
+    fn materiel(&self, half: &HalfBitBoard) -> Millipawns {
+        -half.materiel()
+    }
+
+    fn game_end<'a>(
+        &self,
+        board: &BitBoard,
+        moves: &[Move],
+        hash: HashResult,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Option<GameEnd> {
+        if moves.is_empty() {
+            Some(GameEnd::from_color(board.metadata.to_move))
+        } else {
+            GameEnd::determine(self, board, moves, hash, change, three)
+        }
+    }
+}
+
+#[test]
+fn antichess_perft() {
+    let board = Antichess.start();
+    assert_eq!(Antichess.perft(&board, 1), 20);
+    assert_eq!(Antichess.perft(&board, 2), 400);
+    assert_eq!(Antichess.perft(&board, 3), 8_067);
+    assert_eq!(Antichess.perft(&board, 4), 153_299);
+}
+
+#[test]
+fn antichess_forced_captures() {
+    let mut board = Antichess.start();
+    let mut moves = vec![];
+
+    for pm in [Square::e2.to(Square::e3), Square::b7.to(Square::b5)] {
+        moves.clear();
+        Antichess.generate_moves(&board, &mut moves);
+        Antichess.apply(&mut board, *moves.iter().find(|m| m.from_to == pm).unwrap());
+    }
+
+    moves.clear();
+    Antichess.generate_moves(&board, &mut moves);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].from_to, Square::f1.to(Square::b5));
+
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::Antichess;
+    board.set_piece(Some(ColorPiece::WhitePawn), Square::c7);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::a1);
+
+    moves.clear();
+    Antichess.generate_moves(&board, &mut moves);
+    assert!(moves.iter().any(|m| m.promotion == Some(Piece::King)));
+}
+
+#[test]
+fn antichess_victory() {
+    let three = ThreefoldRule::empty();
+    let change = LastChange::start();
+    let mut moves = vec![];
+
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::Antichess;
+    board.set_piece(Some(ColorPiece::WhitePawn), Square::a4);
+    board.set_piece(Some(ColorPiece::BlackPawn), Square::a5);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::h8);
+
+    Antichess.generate_moves(&board, &mut moves);
+    assert_eq!(
+        Antichess.game_end(&board, &moves, 0, &change, &three),
+        Some(GameEnd::WhiteWins)
+    );
+
+    board.set_piece(None, Square::a4);
+    moves.clear();
+    Antichess.generate_moves(&board, &mut moves);
+    assert_eq!(
+        Antichess.game_end(&board, &moves, 0, &change, &three),
+        Some(GameEnd::WhiteWins)
+    );
+
+    board.set_piece(Some(ColorPiece::WhiteBishop), Square::a1);
+    board.set_piece(None, Square::a5);
+    board.set_piece(None, Square::h8);
+    board.set_piece(Some(ColorPiece::BlackBishop), Square::a2);
+    moves.clear();
+    Antichess.generate_moves(&board, &mut moves);
+    assert_eq!(
+        Antichess.game_end(&board, &moves, 0, &change, &three),
+        Some(GameEnd::Draw)
+    );
+}
+
+/// Captures blow up every piece next to them but pawns, and a move may not
+/// blow up one's own king.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Atomic;
+
+impl Atomic {
+    /// Kings cannot capture in atomic chess, so the enemy king gives no check
+    /// and standing next to it shields from all checks.
+    fn check(defender: &HalfBitBoard, attacker: &HalfBitBoard, attacker_color: Color) -> bool {
+        let king = defender.kings.as_mask();
+        if (attacker.kings.threats() & king).any() {
+            return false;
+        }
+
+        let mut attacker = attacker.clone();
+        attacker.kings = Kings::nil();
+        (king & attacker.threats(attacker_color, defender.as_mask(), None)).any()
+    }
+
+    /// Both sides after `mv` and its explosion, mover first.
+    fn after(board: &BitBoard, mv: Move) -> (HalfBitBoard, HalfBitBoard) {
+        let color = mv.color_and_piece.color();
+        let mut active = board.color(color).clone();
+        let mut passive = board.color(color.other()).clone();
+        BitBoard::move_pieces(
+            &mut active,
+            &mut passive,
+            mv,
+            board.metadata.castling_details,
+        );
+
+        if mv.capture.is_some() {
+            Explosion::detonate(&mut active, &mut passive, mv.from_to.to);
+        }
+
+        (active, passive)
+    }
+
+    fn legal(board: &BitBoard, mv: Move) -> bool {
+        let color = mv.color_and_piece.color();
+        let (active, passive) = Self::after(board, mv);
+
+        active.kings.as_mask().any()
+            && (!passive.kings.as_mask().any() || !Self::check(&active, &passive, color.other()))
+    }
+
+    /// The pieces blown up by `mv`, if it is a capture.
+    pub fn explosion(board: &BitBoard, mv: Move) -> Option<Explosion> {
+        mv.capture?;
+
+        let mut white = board.white.clone();
+        let mut black = board.black.clone();
+        let (active, passive) = match mv.color_and_piece.color() {
+            Color::White => (&mut white, &mut black),
+            Color::Black => (&mut black, &mut white),
+        };
+        BitBoard::move_pieces(active, passive, mv, board.metadata.castling_details);
+
+        Some(Explosion::detonate(&mut white, &mut black, mv.from_to.to))
+    }
+
+    /// Blows up the pieces around a capture that `apply` has played.
+    fn detonate(board: &mut BitBoard, mv: Move) -> Option<Explosion> {
+        mv.capture?;
+
+        let details = board.metadata.castling_details;
+        let explosion = Explosion::detonate(&mut board.white, &mut board.black, mv.from_to.to);
+        board
+            .metadata
+            .white_castling
+            .update(explosion.castling_rights(details, Color::White));
+        board
+            .metadata
+            .black_castling
+            .update(explosion.castling_rights(details, Color::Black));
+        Some(explosion)
+    }
+}
+
+impl Rules for Atomic {
+    fn name(&self) -> &'static str {
+        Variant::Atomic.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((Atomic, s.strip_prefix(Variant::Atomic.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        standard_start(Variant::Atomic)
+    }
+
+    /// Kings cannot capture, and a move is legal unless it blows up its own
+    /// king or leaves it in check.
+    fn generate_piece_moves(
+        &self,
+        board: &BitBoard,
+        piece: Piece,
+        from: Mask,
+        res: &mut Vec<Move>,
+    ) {
+        let start = res.len();
+        generate_kingless_moves(board, piece, from, Pawns::PROMOTIONS, res);
+        retain_from(res, start, |&mv| {
+            (piece != Piece::King || mv.capture.is_none()) && Self::legal(board, mv)
+        });
+    }
+
+    fn apply(&self, board: &mut BitBoard, mv: Move) -> Undo {
+        let mut undo = board.apply(mv);
+        undo.explosion = Self::detonate(board, mv);
+        undo
+    }
+
+    fn undo(&self, board: &mut BitBoard, mv: Move, undo: &Undo) {
+        if let Some(explosion) = &undo.explosion {
+            explosion.restore(&mut board.white, &mut board.black);
+        }
+        board.undo(mv, undo);
+    }
+
+    /// Takes the exploded pieces and the castling rights they held out of
+    /// the hash as well.
+    fn hash_delta(
+        &self,
+        hasher: &BitBoardHasher,
+        board: &BitBoard,
+        hash: HashResult,
+        mv: Move,
+    ) -> HashResult {
+        let mut hash = hasher.delta(board, hash, mv);
+        let Some(explosion) = Self::explosion(board, mv) else {
+            return hash;
+        };
+
+        let color = mv.color_and_piece.color();
+        let (same, opposite) = (hasher.color(color), hasher.color(color.other()));
+        let details = board.metadata.castling_details;
+        let (mut same_cast, mut opp_cast) = board.metadata.castling_rights(color);
+        let (same_new_cast, opp_new_cast) = mv.castling_rights(details);
+
+        same_cast.update(same_new_cast);
+        opp_cast.update(opp_new_cast);
+        hash ^= same.hash_castle(same_cast) ^ opposite.hash_castle(opp_cast);
+        same_cast.update(explosion.castling_rights(details, color));
+        opp_cast.update(explosion.castling_rights(details, color.other()));
+        hash ^= same.hash_castle(same_cast) ^ opposite.hash_castle(opp_cast);
+
+        hash ^ hasher.white.hash(&explosion.white) ^ hasher.black.hash(&explosion.black)
+    }
+
+    fn is_in_check(&self, board: &BitBoard, color: Color) -> bool {
+        Self::check(
+            board.color(color),
+            board.color(color.other()),
+            color.other(),
+        )
+    }
+
+    fn gives_check(&self, board: &BitBoard, mv: Move) -> bool {
+        let (active, passive) = Self::after(board, mv);
+        passive.kings.as_mask().any() && Self::check(&passive, &active, mv.color_and_piece.color())
+    }
+
+    /// A capture blows up the capturer too, whatever recaptures there are.
+    fn material_exchanges_sound(&self) -> bool {
+        false
+    }
+
+    fn game_end<'a>(
+        &self,
+        board: &BitBoard,
+        moves: &[Move],
+        hash: HashResult,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Option<GameEnd> {
+        if !board.active().kings.as_mask().any() {
+            Some(GameEnd::from_color(board.metadata.to_move.other()))
+        } else {
+            StandardChess.game_end(board, moves, hash, change, three)
+        }
+    }
+}
+
+#[test]
+fn atomic_perft() {
+    let board = Atomic.start();
+    assert_eq!(Atomic.perft(&board, 1), 20);
+    assert_eq!(Atomic.perft(&board, 2), 400);
+    assert_eq!(Atomic.perft(&board, 3), 8902);
+    assert_eq!(Atomic.perft(&board, 4), 197326);
+}
+
+#[test]
+fn atomic_explosions_undo() {
+    use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    let mut rng = StdRng::from_seed(*b"3.141592653589793238462643383279");
+    let mut moves = vec![];
+    let mut explosions = 0;
+
+    for _ in 0..20 {
+        let mut board = Atomic.start();
+        let mut history = vec![];
+
+        for _ in 0..200 {
+            moves.clear();
+            Atomic.generate_moves(&board, &mut moves);
+            let Some(&mv) = moves.choose(&mut rng) else {
+                break;
+            };
+
+            let before = board.clone();
+            let undo = Atomic.apply(&mut board, mv);
+            if mv.capture.is_some() {
+                explosions += 1;
+                assert!(!(board.as_mask() & mv.from_to.to.as_mask()).any());
+            }
+            history.push((before, mv, undo));
+        }
+
+        while let Some((before, mv, undo)) = history.pop() {
+            Atomic.undo(&mut board, mv, &undo);
+            assert_eq!(board.white, before.white);
+            assert_eq!(board.black, before.black);
+            board = before;
+        }
+    }
+
+    assert!(explosions > 0);
+}
+
+#[test]
+fn positions_without_history() {
+    use crate::shessboard::notation::{
+        Algebraic,
+        fen::{read_fen, write_fen},
+    };
+
+    let mut board = Atomic.start();
+    let mut moves = vec![];
+    for s in ["e4", "d5", "exd5"] {
+        moves.clear();
+        Atomic.generate_moves(&board, &mut moves);
+        let (n, "") = Algebraic::read(s).unwrap() else {
+            panic!()
+        };
+        Atomic.apply(&mut board, n.find(&moves)[0]);
+    }
+
+    // the explosion is gone from the position once it has happened
+    let fen = write_fen(&board, &LastChange::start());
+    let ((set_up, _), "") = read_fen(Variant::Atomic, &fen).unwrap() else {
+        panic!()
+    };
+    assert_eq!(set_up, board);
+}
+
+#[test]
+fn atomic_king_explodes() {
+    let three = ThreefoldRule::empty();
+    let change = LastChange::start();
+    let mut moves = vec![];
+
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::Atomic;
+    board.set_piece(Some(ColorPiece::WhiteKing), Square::a1);
+    board.set_piece(Some(ColorPiece::WhiteRook), Square::d1);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::e8);
+    board.set_piece(Some(ColorPiece::BlackKnight), Square::d7);
+
+    Atomic.generate_moves(&board, &mut moves);
+    let mv = *moves
+        .iter()
+        .find(|m| m.from_to == Square::d1.to(Square::d7))
+        .unwrap();
+    Atomic.apply(&mut board, mv);
+
+    moves.clear();
+    Atomic.generate_moves(&board, &mut moves);
+    assert!(moves.is_empty());
+    assert_eq!(
+        Atomic.game_end(&board, &moves, 0, &change, &three),
+        Some(GameEnd::WhiteWins)
+    );
+}
+
+/// Captured pieces go to the capturer's pocket and can be dropped back in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Crazyhouse;
+
+impl Rules for Crazyhouse {
+    fn name(&self) -> &'static str {
+        Variant::Crazyhouse.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((Crazyhouse, s.strip_prefix(Variant::Crazyhouse.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        standard_start(Variant::Crazyhouse)
+    }
+
+    fn generate_moves(&self, board: &BitBoard, res: &mut Vec<Move>) {
+        board.generate_moves(res);
+        board.generate_drops(res);
+    }
+
+    fn apply(&self, board: &mut BitBoard, mv: Move) -> Undo {
+        let mut undo = board.apply(mv);
+        undo.demoted = board.update_pockets(mv, false);
+        undo
+    }
+
+    fn undo(&self, board: &mut BitBoard, mv: Move, undo: &Undo) {
+        board.downdate_pockets(mv, undo.demoted, false);
+        board.undo(mv, undo);
+    }
+
+    fn extra_hash(&self, hasher: &BitBoardHasher, board: &BitBoard) -> HashResult {
+        hash_pockets(hasher, board)
+    }
+
+    fn hash_delta(
+        &self,
+        hasher: &BitBoardHasher,
+        board: &BitBoard,
+        hash: HashResult,
+        mv: Move,
+    ) -> HashResult {
+        hasher.delta(board, hash, mv) ^ pocket_delta(hasher, board, mv, false)
+    }
+
+    fn has_legal_move(&self, board: &BitBoard) -> bool {
+        let mut drops = Vec::with_capacity(50);
+        board.generate_drops(&mut drops);
+        board.has_legal_move() || !drops.is_empty()
+    }
+
+    /// Material in hand can always come back to mate.
+    fn sufficient_material(&self, board: &BitBoard) -> bool {
+        !(board.metadata.white_pocket.is_empty() && board.metadata.black_pocket.is_empty())
+            || board.sufficient_checkmating_materiel()
+    }
+}
+
+#[test]
+fn crazyhouse_perft() {
+    let board = Crazyhouse.start();
+    assert_eq!(Crazyhouse.perft(&board, 1), 20);
+    assert_eq!(Crazyhouse.perft(&board, 2), 400);
+    assert_eq!(Crazyhouse.perft(&board, 3), 8_902);
+    assert_eq!(Crazyhouse.perft(&board, 4), 197_281);
+}
+
+#[test]
+fn crazyhouse_drops() {
+    use crate::shessboard::{
+        notation::{Algebraic, fen::read_fen},
+        pockets::Pocket,
+    };
+    use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+    let ((board, _), "") = read_fen(
+        Variant::Crazyhouse,
+        "r1bqkb1r/pppp1ppp/2n5/4p3/4P3/8/PPPP1PPP/RNBQKB1R[NPn] w KQkq - 0 4",
+    )
+    .unwrap() else {
+        panic!()
+    };
+    let mut moves = vec![];
+    Crazyhouse.generate_moves(&board, &mut moves);
+
+    let empty = (!board.as_mask()).occupied() as usize;
+    let back_ranks = ((Rank::_1.as_mask() | Rank::_8.as_mask()) & !board.as_mask()).occupied();
+    assert_eq!(
+        moves.iter().filter(|m| m.is_drop()).count(),
+        2 * empty - back_ranks as usize
+    );
+
+    let (not, "") = Algebraic::read("N@f3").unwrap() else {
+        panic!()
+    };
+    let [mv] = not.find(&moves)[..] else { panic!() };
+    assert_eq!(Algebraic::new(&mv, &moves).to_string(), "N@f3");
+    assert_eq!(Algebraic::read("P@a1").unwrap().0.find(&moves), vec![]);
+
+    let mut rng = StdRng::from_seed(*b"3.141592653589793238462643383279");
+    let mut drops = 0;
+
+    for _ in 0..20 {
+        let mut board = Crazyhouse.start();
+        let mut history = vec![];
+
+        for _ in 0..200 {
+            moves.clear();
+            Crazyhouse.generate_moves(&board, &mut moves);
+            let Some(&mv) = moves.choose(&mut rng) else {
+                break;
+            };
+
+            drops += mv.is_drop() as usize;
+            let before = board.clone();
+            history.push((before, mv, Crazyhouse.apply(&mut board, mv)));
+
+            let pocketed = Pocket::PIECES
+                .iter()
+                .map(|&p| {
+                    board.metadata.white_pocket.count(p) as u32
+                        + board.metadata.black_pocket.count(p) as u32
+                })
+                .sum::<u32>();
+            assert_eq!(board.as_mask().occupied() + pocketed, 32);
+            assert!(!(board.metadata.promoted & !board.as_mask()).any());
+        }
+
+        while let Some((before, mv, undo)) = history.pop() {
+            Crazyhouse.undo(&mut board, mv, &undo);
+            assert_eq!(board.white, before.white);
+            assert_eq!(board.black, before.black);
+            assert_eq!(board.metadata.white_pocket, before.metadata.white_pocket);
+            assert_eq!(board.metadata.black_pocket, before.metadata.black_pocket);
+            assert_eq!(board.metadata.promoted, before.metadata.promoted);
+            board = before;
+        }
+    }
+
+    assert!(drops > 0);
+}
+
+/// Crazyhouse on one of two boards, where captures go to the partner on the
+/// other board. See [`crate::bughouse::Bughouse`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Bughouse;
+
+impl Rules for Bughouse {
+    fn name(&self) -> &'static str {
+        Variant::Bughouse.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((Bughouse, s.strip_prefix(Variant::Bughouse.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        standard_start(Variant::Bughouse)
+    }
+
+    fn generate_moves(&self, board: &BitBoard, res: &mut Vec<Move>) {
+        Crazyhouse.generate_moves(board, res);
+    }
+
+    fn apply(&self, board: &mut BitBoard, mv: Move) -> Undo {
+        let mut undo = board.apply(mv);
+        undo.demoted = board.update_pockets(mv, true);
+        undo
+    }
+
+    fn undo(&self, board: &mut BitBoard, mv: Move, undo: &Undo) {
+        board.downdate_pockets(mv, undo.demoted, true);
+        board.undo(mv, undo);
+    }
+
+    fn extra_hash(&self, hasher: &BitBoardHasher, board: &BitBoard) -> HashResult {
+        hash_pockets(hasher, board)
+    }
+
+    fn hash_delta(
+        &self,
+        hasher: &BitBoardHasher,
+        board: &BitBoard,
+        hash: HashResult,
+        mv: Move,
+    ) -> HashResult {
+        hasher.delta(board, hash, mv) ^ pocket_delta(hasher, board, mv, true)
+    }
+
+    fn has_legal_move(&self, board: &BitBoard) -> bool {
+        Crazyhouse.has_legal_move(board)
+    }
+
+    /// A partner can always pass more material.
+    fn sufficient_material(&self, board: &BitBoard) -> bool {
+        true
+    }
+}
+
+/// Thirty-six white pawns against a full black army. Black wins by
+/// capturing them all.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Horde;
+
+impl Rules for Horde {
+    fn name(&self) -> &'static str {
+        Variant::Horde.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((Horde, s.strip_prefix(Variant::Horde.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        let mut board = standard_start(Variant::Horde);
+        board.white = HalfBitBoard::nil();
+        board.white.pawns = Pawns::new(Pawns::HORDE);
+        board.metadata.white_castling = NO_CASTLING;
+        board
+    }
+
+    /// Pawns of a side without a king may also step twice from the first
+    /// rank.
+    fn generate_piece_moves(
+        &self,
+        board: &BitBoard,
+        piece: Piece,
+        from: Mask,
+        res: &mut Vec<Move>,
+    ) {
+        board.generate_piece_moves(piece, from, res);
+        if piece == Piece::Pawn && !board.active().kings.as_mask().any() {
+            Pawns::new(from).enumerate_first_rank_double_steps(
+                board.metadata.to_move,
+                board.as_mask(),
+                board.metadata.en_passant.map(|ep| ep.to),
+                res,
+            );
+        }
+    }
+
+    /// The horde can always be captured.
+    fn sufficient_material(&self, board: &BitBoard) -> bool {
+        true
+    }
+
+    fn game_end<'a>(
+        &self,
+        board: &BitBoard,
+        moves: &[Move],
+        hash: HashResult,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Option<GameEnd> {
+        if !board.white.as_mask().any() {
+            Some(GameEnd::BlackWins)
+        } else {
+            GameEnd::determine(self, board, moves, hash, change, three)
+        }
+    }
+}
+
+#[test]
+fn horde_perft() {
+    let board = Horde.start();
+    assert_eq!(board.white.pawns.as_mask().occupied(), 36);
+    assert_eq!(Horde.perft(&board, 1), 8);
+    assert_eq!(Horde.perft(&board, 2), 128);
+    assert_eq!(Horde.perft(&board, 3), 1_274);
+    assert_eq!(Horde.perft(&board, 4), 23_310);
+
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::Horde;
+    board.set_piece(Some(ColorPiece::WhitePawn), Square::e1);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::a8);
+
+    let mut moves = vec![];
+    Horde.generate_moves(&board, &mut moves);
+    assert_eq!(moves.len(), 2);
+    for mv in moves {
+        assert_eq!(mv.en_passant_square(), None);
+    }
+}
+
+#[test]
+fn horde_captured() {
+    let three = ThreefoldRule::empty();
+    let change = LastChange::start();
+    let mut moves = vec![];
+
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::Horde;
+    board.set_piece(Some(ColorPiece::WhitePawn), Square::b7);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::a8);
+    board.metadata.to_move = Color::Black;
+
+    Horde.generate_moves(&board, &mut moves);
+    assert_eq!(Horde.game_end(&board, &moves, 0, &change, &three), None);
+
+    let mv = *moves
+        .iter()
+        .find(|m| m.from_to == Square::a8.to(Square::b7))
+        .unwrap();
+    Horde.apply(&mut board, mv);
+    moves.clear();
+    Horde.generate_moves(&board, &mut moves);
+    assert_eq!(
+        Horde.game_end(&board, &moves, 0, &change, &three),
+        Some(GameEnd::BlackWins)
+    );
+}
+
+/// The first king to reach the eighth rank wins, and no move may give check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RacingKings;
+
+impl RacingKings {
+    /// Black gets one more move to draw after White's king reaches the
+    /// eighth rank.
+    fn race(board: &BitBoard) -> Option<GameEnd> {
+        let goal = Rank::_8.as_mask();
+        let white = board.white.kings.as_mask().overlap(goal).any();
+        let black = board.black.kings.as_mask().overlap(goal).any();
+
+        match (white, black) {
+            (true, true) => Some(GameEnd::Draw),
+            (false, true) => Some(GameEnd::BlackWins),
+            (true, false)
+                if board.metadata.to_move == Color::Black && Self::black_can_follow(board) =>
+            {
+                None
+            }
+            (true, false) => Some(GameEnd::WhiteWins),
+            (false, false) => None,
+        }
+    }
+
+    fn black_can_follow(board: &BitBoard) -> bool {
+        let mut moves = Vec::with_capacity(8);
+        RacingKings.generate_piece_moves(
+            board,
+            Piece::King,
+            board.black.kings.as_mask(),
+            &mut moves,
+        );
+        moves.iter().any(|mv| mv.from_to.to.rank() == Rank::_8)
+    }
+}
+
+impl Rules for RacingKings {
+    fn name(&self) -> &'static str {
+        Variant::RacingKings.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((RacingKings, s.strip_prefix(Variant::RacingKings.name())?))
+    }
+
+    /// Both sides start on the first two ranks, without pawns.
+    fn start(&self) -> BitBoard {
+        use ColorPiece::*;
+        let mut pieces = BoardMap::new_with(None);
+        for (file, (first, second)) in [
+            (BlackQueen, BlackKing),
+            (BlackRook, BlackRook),
+            (BlackBishop, BlackBishop),
+            (BlackKnight, BlackKnight),
+            (WhiteKnight, WhiteKnight),
+            (WhiteBishop, WhiteBishop),
+            (WhiteRook, WhiteRook),
+            (WhiteQueen, WhiteKing),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let file = File::file(file as i8).unwrap();
+            pieces.set(Square::at(file, Rank::_1), Some(first));
+            pieces.set(Square::at(file, Rank::_2), Some(second));
+        }
+
+        let mut board = standard_start(Variant::RacingKings);
+        board.white = HalfBitBoard::new(&pieces, Color::White);
+        board.black = HalfBitBoard::new(&pieces, Color::Black);
+        board.metadata.white_castling = NO_CASTLING;
+        board.metadata.black_castling = NO_CASTLING;
+        board
+    }
+
+    fn generate_piece_moves(
+        &self,
+        board: &BitBoard,
+        piece: Piece,
+        from: Mask,
+        res: &mut Vec<Move>,
+    ) {
+        let start = res.len();
+        board.generate_piece_moves(piece, from, res);
+        retain_from(res, start, |&mv| !board.gives_check(mv));
+    }
+
+    /// Racing needs no mate.
+    fn sufficient_material(&self, board: &BitBoard) -> bool {
+        true
+    }
+
+    fn game_end<'a>(
+        &self,
+        board: &BitBoard,
+        moves: &[Move],
+        hash: HashResult,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Option<GameEnd> {
+        if let Some(end) = Self::race(board) {
+            Some(end)
+        } else {
+            GameEnd::determine(self, board, moves, hash, change, three)
+        }
+    }
+}
+
+#[test]
+fn racing_kings_perft() {
+    let board = RacingKings.start();
+    assert!(!board.white.pawns.as_mask().any() && !board.black.pawns.as_mask().any());
+    assert_eq!(RacingKings.perft(&board, 1), 21);
+    assert_eq!(RacingKings.perft(&board, 2), 421);
+    assert_eq!(RacingKings.perft(&board, 3), 11_264);
+    assert_eq!(RacingKings.perft(&board, 4), 296_242);
+}
+
+#[test]
+fn racing_kings() {
+    let three = ThreefoldRule::empty();
+    let change = LastChange::start();
+
+    let play = |board: &mut BitBoard, from: Square, to: Square| {
+        let mut moves = vec![];
+        RacingKings.generate_moves(board, &mut moves);
+        let mv = *moves.iter().find(|m| m.from_to == from.to(to)).unwrap();
+        RacingKings.apply(board, mv);
+        moves.clear();
+        RacingKings.generate_moves(board, &mut moves);
+        RacingKings.game_end(board, &moves, 0, &change, &three)
+    };
+
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::RacingKings;
+    board.set_piece(Some(ColorPiece::WhiteKing), Square::a7);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::h6);
+    board.set_piece(Some(ColorPiece::WhiteRook), Square::b1);
+
+    let mut moves = vec![];
+    RacingKings.generate_moves(&board, &mut moves);
+    assert!(!moves.iter().any(|m| board.gives_check(*m)));
+    assert!(!moves.iter().any(|m| m.from_to == Square::b1.to(Square::h1)));
+
+    let mut b = board.clone();
+    assert_eq!(
+        play(&mut b, Square::a7, Square::a8),
+        Some(GameEnd::WhiteWins)
+    );
+
+    board.set_piece(None, Square::h6);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::g7);
+    let mut b = board.clone();
+    assert_eq!(play(&mut b, Square::a7, Square::a8), None);
+    assert_eq!(
+        play(&mut b, Square::g7, Square::f6),
+        Some(GameEnd::WhiteWins)
+    );
+
+    assert_eq!(play(&mut board, Square::a7, Square::a8), None);
+    assert_eq!(
+        play(&mut board, Square::g7, Square::g8),
+        Some(GameEnd::Draw)
+    );
+}
+
+/// Each side sees only what its pieces reach, and wins by taking the king.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FogOfWar;
+
+impl Rules for FogOfWar {
+    fn name(&self) -> &'static str {
+        Variant::FogOfWar.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((FogOfWar, s.strip_prefix(Variant::FogOfWar.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        standard_start(Variant::FogOfWar)
+    }
+
+    /// The king is taken rather than mated, so it may step into attacks.
+    fn generate_piece_moves(
+        &self,
+        board: &BitBoard,
+        piece: Piece,
+        from: Mask,
+        res: &mut Vec<Move>,
+    ) {
+        generate_kingless_moves(board, piece, from, Pawns::PROMOTIONS, res);
+    }
+
+    fn gives_check(&self, board: &BitBoard, mv: Move) -> bool {
+        false
+    }
+
+    fn game_end<'a>(
+        &self,
+        board: &BitBoard,
+        moves: &[Move],
+        hash: HashResult,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Option<GameEnd> {
+        if !board.active().kings.as_mask().any() {
+            Some(GameEnd::from_color(board.metadata.to_move.other()))
+        } else {
+            StandardChess.game_end(board, moves, hash, change, three)
+        }
+    }
+}
+
+/// A royal knight and two commoner kings take each other's places.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Knightmate;
+
+impl Rules for Knightmate {
+    fn name(&self) -> &'static str {
+        Variant::Knightmate.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((Knightmate, s.strip_prefix(Variant::Knightmate.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        use Piece::*;
+        let mut metadata = Metadata::new();
+        metadata.variant = Variant::Knightmate;
+        BitBoard::new_starting_array(
+            [Rook, King, Bishop, Queen, Knight, Bishop, King, Rook],
+            metadata,
+        )
+    }
+
+    fn royal(&self) -> Piece {
+        Piece::Knight
+    }
+
+    /// The royal knight castles, and the commoner kings may step into
+    /// attacks.
+    fn generate_piece_moves(
+        &self,
+        board: &BitBoard,
+        piece: Piece,
+        from: Mask,
+        res: &mut Vec<Move>,
+    ) {
+        let start = res.len();
+        let promotions = Pawns::KNIGHTMATE_PROMOTIONS;
+
+        match piece {
+            Piece::Knight => {
+                board.generate_guarded_moves(piece, from, Kings::nil(), promotions, res);
+                board.generate_castling_moves(piece, from, res);
+                retain_from(res, start, |&mv| !board.exposes(mv, Piece::Knight));
+            }
+            Piece::King => {
+                board.generate_commoner_moves(from, res);
+                retain_from(res, start, |&mv| !board.exposes(mv, Piece::Knight));
+            }
+            _ => {
+                let royal = Kings::new(board.active().knights.as_mask());
+                board.generate_guarded_moves(piece, from, royal, promotions, res);
+            }
+        }
+    }
+
+    /// Commoners can help the other pieces mate, so only bare royal knights
+    /// are a draw.
+    fn sufficient_material(&self, board: &BitBoard) -> bool {
+        board.as_mask().occupied() > 2
+    }
+}
+
+#[test]
+fn knightmate_perft() {
+    let board = Knightmate.start();
+    assert_eq!(board.white.knights.as_mask(), Square::e1.as_mask());
+    assert_eq!(Knightmate.perft(&board, 1), 18);
+    assert_eq!(Knightmate.perft(&board, 2), 324);
+    assert_eq!(Knightmate.perft(&board, 3), 6_765);
+    assert_eq!(Knightmate.perft(&board, 4), 139_774);
+}
+
+#[test]
+fn knightmate_royalty() {
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::Knightmate;
+    board.metadata.white_castling = CastlingRights {
+        ooo: false,
+        oo: true,
+    };
+    board.set_piece(Some(ColorPiece::WhiteKnight), Square::e1);
+    board.set_piece(Some(ColorPiece::WhiteRook), Square::h1);
+    board.set_piece(Some(ColorPiece::WhiteKing), Square::d4);
+    board.set_piece(Some(ColorPiece::BlackKnight), Square::e6);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::c3);
+    assert!(board.sufficient_checkmating_materiel());
+
+    let mut moves = vec![];
+    Knightmate.generate_moves(&board, &mut moves);
+
+    // the royal knight castles and may not step next to the enemy commoner
+    let castle = *moves.iter().find(|mv| mv.castling.is_some()).unwrap();
+    assert_eq!(castle.color_and_piece, ColorPiece::WhiteKnight);
+    assert!(
+        !moves
+            .iter()
+            .any(|mv| mv.from_to == Square::e1.to(Square::d3))
+    );
+
+    // commoners give check and may walk into attacks
+    let step = *moves
+        .iter()
+        .find(|mv| mv.from_to == Square::d4.to(Square::d5))
+        .unwrap();
+    assert!(Knightmate.gives_check(&board, step));
+    assert!(
+        moves
+            .iter()
+            .any(|mv| mv.from_to == Square::d4.to(Square::c4))
+    );
+
+    Knightmate.apply(&mut board, castle);
+    assert_eq!(board.white.knights.as_mask(), Square::g1.as_mask());
+    assert_eq!(board.white.rooks.as_mask(), Square::f1.as_mask());
+    assert!(!board.metadata.white_castling.oo);
+}
+
+/// Losing every piece of some kind loses the game.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Extinction;
+
+/// Penalty for being down to the last piece of a type in extinction chess,
+/// since losing that piece loses the game.
+const LAST_OF_KIND: Millipawns = 20 * P;
+
+impl Rules for Extinction {
+    fn name(&self) -> &'static str {
+        Variant::Extinction.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((Extinction, s.strip_prefix(Variant::Extinction.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        standard_start(Variant::Extinction)
+    }
+
+    /// Nothing is royal, and pawns may also promote to kings.
+    fn generate_piece_moves(
+        &self,
+        board: &BitBoard,
+        piece: Piece,
+        from: Mask,
+        res: &mut Vec<Move>,
+    ) {
+        generate_kingless_moves(board, piece, from, Pawns::ANTICHESS_PROMOTIONS, res);
+    }
+
+    fn is_in_check(&self, board: &BitBoard, color: Color) -> bool {
+        false
+    }
+
+    fn gives_check(&self, board: &BitBoard, mv: Move) -> bool {
+        false
+    }
+
+    fn sufficient_material(&self, board: &BitBoard) -> bool {
+        true
+    }
+
+    /// Every piece type is one to lose, so having to move is rarely harmless.
+    fn null_move_sound(&self) -> bool {
+        false
+    }
+
+    /// Each type must survive, and the last piece of every type is guarded
+    /// jealously.
+    fn materiel(&self, half: &HalfBitBoard) -> Millipawns {
+        use Piece::*;
+        let mut res = 0;
+
+        for piece in [King, Queen, Rook, Bishop, Knight, Pawn] {
+            res += match half.piece_mask(piece).occupied() {
+                0 => return GameEnd::DEFEAT,
+                1 => piece.materiel() - LAST_OF_KIND,
+                n => n as Millipawns * piece.materiel(),
+            };
+        }
+
+        res
+    }
+
+    fn game_end<'a>(
+        &self,
+        board: &BitBoard,
+        moves: &[Move],
+        hash: HashResult,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Option<GameEnd> {
+        if board.active().extinct().is_some() {
+            Some(GameEnd::from_color(board.metadata.to_move.other()))
+        } else if board.passive().extinct().is_some() {
+            // promoting the last pawn wipes out one's own pawns
+            Some(GameEnd::from_color(board.metadata.to_move))
+        } else {
+            GameEnd::determine(self, board, moves, hash, change, three)
+        }
+    }
+}
+
+#[test]
+fn last_of_kind() {
+    let mut half = BitBoard::new().white;
+    let start = Extinction.materiel(&half);
+
+    half.delete(Square::a2);
+    assert_eq!(Extinction.materiel(&half), start - P);
+
+    // the second rook is worth its materiel and the safety of the first
+    half.delete(Square::a1);
+    assert_eq!(
+        Extinction.materiel(&half),
+        start - P - Piece::Rook.materiel() - LAST_OF_KIND
+    );
+
+    half.delete(Square::d1);
+    assert_eq!(Extinction.materiel(&half), GameEnd::DEFEAT);
+}
+
+#[test]
+fn extinction_perft() {
+    let board = Extinction.start();
+    assert_eq!(Extinction.perft(&board, 1), 20);
+    assert_eq!(Extinction.perft(&board, 2), 400);
+    assert_eq!(Extinction.perft(&board, 3), 8_902);
+    assert_eq!(Extinction.perft(&board, 4), 197_742);
+}
+
+#[test]
+fn extinction() {
+    let three = ThreefoldRule::empty();
+    let change = LastChange::start();
+    let mut moves = vec![];
+
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::Extinction;
+    for (cp, sq) in [
+        (ColorPiece::WhiteKing, Square::e1),
+        (ColorPiece::WhiteQueen, Square::d1),
+        (ColorPiece::WhiteRook, Square::h1),
+        (ColorPiece::WhiteBishop, Square::c1),
+        (ColorPiece::WhiteKnight, Square::b1),
+        (ColorPiece::WhitePawn, Square::b7),
+        (ColorPiece::BlackKing, Square::e8),
+        (ColorPiece::BlackQueen, Square::d8),
+        (ColorPiece::BlackRook, Square::a8),
+        (ColorPiece::BlackBishop, Square::f8),
+        (ColorPiece::BlackKnight, Square::g8),
+        (ColorPiece::BlackPawn, Square::h7),
+    ] {
+        board.set_piece(Some(cp), sq);
+    }
+
+    Extinction.generate_moves(&board, &mut moves);
+    assert_eq!(
+        Extinction.game_end(&board, &moves, 0, &change, &three),
+        None
+    );
+
+    for (to, promotion, end) in [
+        // the last pawn becomes a king, and white runs out of pawns
+        (Square::b8, Piece::King, GameEnd::BlackWins),
+        // taking the last rook wins even though it also uses up the last pawn
+        (Square::a8, Piece::Queen, GameEnd::WhiteWins),
+    ] {
+        let mv = *moves
+            .iter()
+            .find(|m| m.from_to.to == to && m.promotion == Some(promotion))
+            .unwrap();
+        let mut after = board.clone();
+        Extinction.apply(&mut after, mv);
+        let mut replies = vec![];
+        Extinction.generate_moves(&after, &mut replies);
+        assert_eq!(
+            Extinction.game_end(&after, &replies, 0, &change, &three),
+            Some(end)
+        );
+    }
+}
+
+/// Each side moves twice a turn, unless its first move gives check.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Marseillais;
+
+impl Marseillais {
+    /// Hands the mover its second move, unless the first one gave check or
+    /// this was already the second.
+    fn next_sub_turn(board: &mut BitBoard, mv: Move) {
+        let color = mv.color_and_piece.color();
+        board.metadata.sub_turn = board.metadata.sub_turn.next(
+            mv,
+            board.is_in_check(color.other()),
+            board.color(color).pawns.as_mask(),
+        );
+        if board.metadata.sub_turn.second {
+            board.metadata.to_move = color;
+            board.metadata.half_turns -= 1;
+        }
+    }
+
+    /// Takes back the sub-turn that `next_sub_turn` moved on to.
+    fn previous_sub_turn(board: &mut BitBoard, mv: Move, sub_turn: SubTurn) {
+        if board.metadata.sub_turn.second {
+            board.metadata.to_move = mv.color_and_piece.color().other();
+            board.metadata.half_turns += 1;
+        }
+        board.metadata.sub_turn = sub_turn;
+    }
+}
+
+impl Rules for Marseillais {
+    fn name(&self) -> &'static str {
+        Variant::Marseillais.name()
+    }
+
+    fn read(s: &str) -> Option<(Self, &str)> {
+        Some((Marseillais, s.strip_prefix(Variant::Marseillais.name())?))
+    }
+
+    fn start(&self) -> BitBoard {
+        standard_start(Variant::Marseillais)
+    }
+
+    /// En passant is only open on the first move of a turn, but then
+    /// against either of the opponent's double steps.
+    fn generate_piece_moves(
+        &self,
+        board: &BitBoard,
+        piece: Piece,
+        from: Mask,
+        res: &mut Vec<Move>,
+    ) {
+        if piece != Piece::Pawn {
+            board.generate_piece_moves(piece, from, res);
+            return;
+        }
+
+        let start = res.len();
+        let color = board.metadata.to_move;
+        let active_mask = board.active().as_mask();
+        let passive_mask = board.passive().as_mask();
+        let kings = board.active().kings;
+        let sub_turn = board.metadata.sub_turn;
+        let (en_passant, first) = if sub_turn.second {
+            (None, None)
+        } else {
+            (board.metadata.en_passant, sub_turn.first_en_passant)
+        };
+
+        Pawns::new(from).enumerate_legal_moves(
+            color,
+            active_mask,
+            passive_mask,
+            board.passive(),
+            en_passant,
+            kings,
+            Pawns::PROMOTIONS,
+            res,
+        );
+
+        if let Some(ep) = first {
+            let extra = res.len();
+            Pawns::new(from).enumerate_legal_moves(
+                color,
+                active_mask,
+                passive_mask,
+                board.passive(),
+                Some(ep),
+                kings,
+                Pawns::PROMOTIONS,
+                res,
+            );
+            retain_from(res, extra, |mv| {
+                mv.from_to.to == ep.to && mv.capture.is_some()
+            });
+        }
+
+        let prev_epc = board.metadata.en_passant.map(|ep| ep.to);
+        for mv in &mut res[start..] {
+            mv.prev_epc = prev_epc;
+        }
+    }
+
+    fn apply(&self, board: &mut BitBoard, mv: Move) -> Undo {
+        let undo = board.apply(mv);
+        Self::next_sub_turn(board, mv);
+        undo
+    }
+
+    fn undo(&self, board: &mut BitBoard, mv: Move, undo: &Undo) {
+        Self::previous_sub_turn(board, mv, undo.sub_turn);
+        board.undo(mv, undo);
+    }
+
+    /// A pass hands over only half a turn.
+    fn null_move_sound(&self) -> bool {
+        false
+    }
+
+    fn extra_hash(&self, hasher: &BitBoardHasher, board: &BitBoard) -> HashResult {
+        hasher.hash_sub_turn(board.metadata.sub_turn)
+    }
+
+    /// Leaves the move with the mover when it has its second move to come.
+    fn hash_delta(
+        &self,
+        hasher: &BitBoardHasher,
+        board: &BitBoard,
+        hash: HashResult,
+        mv: Move,
+    ) -> HashResult {
+        let color = mv.color_and_piece.color();
+        let pawns = board.color(color).pawns.as_mask().unset(mv.from_to.from);
+        let sub_turn = board
+            .metadata
+            .sub_turn
+            .next(mv, self.gives_check(board, mv), pawns);
+        let hash = hasher.delta(board, hash, mv)
+            ^ hasher.hash_sub_turn(board.metadata.sub_turn)
+            ^ hasher.hash_sub_turn(sub_turn);
+
+        if sub_turn.second {
+            hash ^ BitBoardHasher::BLACK_TO_MOVE
+        } else {
+            hash
+        }
+    }
+}
+
+#[test]
+fn marseillais_turns() {
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::Marseillais;
+    for (cp, sq) in [
+        (ColorPiece::WhiteKing, Square::e1),
+        (ColorPiece::WhiteQueen, Square::h1),
+        (ColorPiece::WhitePawn, Square::c2),
+        (ColorPiece::WhitePawn, Square::e2),
+        (ColorPiece::BlackKing, Square::e8),
+        (ColorPiece::BlackPawn, Square::d4),
+        (ColorPiece::BlackPawn, Square::f4),
+    ] {
+        board.set_piece(Some(cp), sq);
+    }
+
+    let play = |board: &mut BitBoard, from: Square, to: Square| {
+        let mut moves = vec![];
+        Marseillais.generate_moves(board, &mut moves);
+        let mv = *moves
+            .iter()
+            .find(|mv| mv.from_to == from.to(to))
+            .unwrap_or_else(|| panic!("{from}{to} is not legal"));
+        (mv, Marseillais.apply(board, mv))
+    };
+
+    play(&mut board, Square::c2, Square::c4);
+    assert_eq!(board.metadata.to_move, Color::White);
+    assert!(board.metadata.sub_turn.second);
+
+    play(&mut board, Square::e2, Square::e4);
+    assert_eq!(board.metadata.to_move, Color::Black);
+    assert_eq!(board.metadata.turn(), 1);
+
+    // both double steps can be taken, but only on the first move
+    let mut moves = vec![];
+    Marseillais.generate_moves(&board, &mut moves);
+    assert!(
+        moves
+            .iter()
+            .any(|mv| mv.from_to == Square::d4.to(Square::c3))
+    );
+    assert!(
+        moves
+            .iter()
+            .any(|mv| mv.from_to == Square::f4.to(Square::e3))
+    );
+
+    let before = board.clone();
+    let (mv, undo) = play(&mut board, Square::d4, Square::c3);
+    assert!(!board.white.pawns.as_mask().contains(Square::c4));
+    moves.clear();
+    Marseillais.generate_moves(&board, &mut moves);
+    assert!(
+        !moves
+            .iter()
+            .any(|mv| mv.from_to == Square::f4.to(Square::e3))
+    );
+
+    let mut undone = board.clone();
+    Marseillais.undo(&mut undone, mv, &undo);
+    assert_eq!(undone, before);
+
+    // a check on the first move ends the turn
+    play(&mut board, Square::e8, Square::d7);
+    assert_eq!(board.metadata.turn(), 2);
+    play(&mut board, Square::h1, Square::h3);
+    assert_eq!(board.metadata.to_move, Color::Black);
+    assert!(!board.metadata.sub_turn.second);
+    assert_eq!(board.metadata.turn(), 2);
+    assert_eq!(board.metadata.tempo, 5);
+}
@@ -15,15 +15,16 @@ use crate::shessboard::{
     BitBoard, CastlingInfo, CastlingRights,
     boardmap::BoardMap,
     castling::CastlingSide,
-    enums::{Color, ColorPiece, Piece},
+    enums::{Color, ColorPiece, Piece, Variant},
     half::HalfBitBoard,
     masks::Mask,
-    metadata::Metadata,
+    metadata::{Metadata, SubTurn},
     moves::Move,
     pieces::{
         Millipawns,
         pawns::{EnPassant, Pawns},
     },
+    pockets::Pocket,
     squares::Square,
 };
 
@@ -60,6 +61,8 @@ pub struct BitBoardHasher {
     pub en_passant_file: [HashResult; 8],
     pub white: HalfBitBoardHasher,
     pub black: HalfBitBoardHasher,
+    pub second_move: HashResult,
+    pub first_en_passant_file: [HashResult; 8],
 }
 
 impl BitBoardHasher {
@@ -82,8 +85,24 @@ impl BitBoardHasher {
             ^ self.black.hash(&board.black)
     }
 
+    pub fn color(&self, color: Color) -> &HalfBitBoardHasher {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+
+    pub fn hash_sub_turn(&self, sub_turn: SubTurn) -> HashResult {
+        let first = match sub_turn.first_en_passant {
+            Some(EnPassant { to }) => self.first_en_passant_file[to.file().as_file() as usize],
+            None => 0,
+        };
+
+        first ^ if sub_turn.second { self.second_move } else { 0 }
+    }
+
     pub fn hash_en_passant(&self, en_passant: Option<EnPassant>) -> HashResult {
-        if let Some(EnPassant { to, capture }) = en_passant {
+        if let Some(EnPassant { to }) = en_passant {
             self.en_passant_file[to.file().as_file() as usize] //.clone()
         } else {
             0
@@ -101,20 +120,26 @@ impl BitBoardHasher {
         }
     }
 
-    pub fn delta(&self, metadata: &Metadata, mut hash: HashResult, mv: Move) -> HashResult {
+    pub fn delta(&self, board: &BitBoard, mut hash: HashResult, mv: Move) -> HashResult {
+        let metadata = &board.metadata;
         let (color, piece) = mv.color_and_piece.split();
-
-        let (same, opposite) = match color {
-            Color::White => (&self.white, &self.black),
-            Color::Black => (&self.black, &self.white),
-        };
+        let (same, opposite) = (self.color(color), self.color(color.other()));
 
         hash &= Self::HASH_BITS;
-        hash ^= Self::hash_to_move(color.other());
 
-        if let Some(p) = mv.promotion {
+        if mv.is_drop() {
+            hash ^= same.hash_piece(piece, mv.from_to.to);
+        } else if let Some(p) = mv.promotion {
             hash ^=
                 same.hash_piece(Piece::Pawn, mv.from_to.from) ^ same.hash_piece(p, mv.from_to.to)
+        } else if let Some(cs) = mv.castling {
+            let detail = metadata.castling_details.select(cs);
+            let king = detail.king_move.as_move(color.starting_rank());
+            let rook = detail.rook_move.as_move(color.starting_rank());
+            hash ^= same.hash_piece(piece, king.from)
+                ^ same.hash_piece(piece, king.to)
+                ^ same.hash_piece(Piece::Rook, rook.from)
+                ^ same.hash_piece(Piece::Rook, rook.to);
         } else {
             hash ^= same.hash_piece(piece, mv.from_to.from) ^ same.hash_piece(piece, mv.from_to.to);
         }
@@ -123,15 +148,6 @@ impl BitBoardHasher {
             hash ^= opposite.hash_piece(p, sq);
         }
 
-        if let Some(cs) = mv.castling {
-            let cast = metadata
-                .castling_details
-                .select(cs)
-                .rook_move
-                .as_move(color.starting_rank());
-            hash ^= same.hash_piece(Piece::Rook, cast.from) ^ same.hash_piece(Piece::Rook, cast.to)
-        }
-
         let (mut same_cast, mut opp_cast) = metadata.castling_rights(color);
         let (same_new_cast, opp_new_cast) = mv.castling_rights(metadata.castling_details);
 
@@ -142,7 +158,8 @@ impl BitBoardHasher {
 
         hash ^= self.hash_en_passant(metadata.en_passant)
             ^ self.hash_en_passant(mv.en_passant_square());
-        hash
+
+        hash ^ Self::hash_to_move(color.other())
     }
 }
 
@@ -152,6 +169,9 @@ impl Fill for BitBoardHasher {
         self.en_passant_file = self.en_passant_file.map(|x| x & Self::HASH_BITS);
         self.white.fill(rng);
         self.black.fill(rng);
+        self.second_move = rng.random::<HashResult>() & Self::HASH_BITS;
+        self.first_en_passant_file.fill(rng);
+        self.first_en_passant_file = self.first_en_passant_file.map(|x| x & Self::HASH_BITS);
     }
 }
 
@@ -159,6 +179,8 @@ impl Fill for BitBoardHasher {
 pub struct HalfBitBoardHasher {
     // pub color: Color,
     pub castling: CastlingInfo<HashResult>,
+    pub checks: [HashResult; 3],
+    pub pockets: [[HashResult; 16]; 5],
     pub kings: MaskHasher,
     pub queens: MaskHasher,
     pub rooks: MaskHasher,
@@ -192,6 +214,28 @@ impl HalfBitBoardHasher {
         self.hasher_for_piece(piece).hash_square(square)
     }
 
+    pub fn hash_checks(&self, checks: u8) -> HashResult {
+        match checks {
+            0 => 0,
+            n => self.checks[(n as usize - 1).min(2)],
+        }
+    }
+
+    pub fn hash_pocket_count(&self, piece: Piece, count: u8) -> HashResult {
+        match count {
+            0 => 0,
+            n => self.pockets[piece as usize - 1][(n as usize - 1).min(15)],
+        }
+    }
+
+    pub fn hash_pocket(&self, pocket: &Pocket) -> HashResult {
+        let mut res = 0;
+        for piece in Pocket::PIECES {
+            res ^= self.hash_pocket_count(piece, pocket.count(piece));
+        }
+        res
+    }
+
     pub fn hash_castle(&self, castling: CastlingRights) -> HashResult {
         (if castling.ooo { self.castling.ooo } else { 0 })
             ^ (if castling.oo { self.castling.oo } else { 0 })
@@ -211,5 +255,128 @@ impl Fill for HalfBitBoardHasher {
         self.bishops.fill(rng);
         self.knights.fill(rng);
         self.pawns.fill(rng);
+
+        self.checks.fill(rng);
+        self.checks = self.checks.map(|x| x & BitBoardHasher::HASH_BITS);
+
+        for counts in &mut self.pockets {
+            counts.fill(rng);
+            *counts = counts.map(|x| x & BitBoardHasher::HASH_BITS);
+        }
     }
 }
+
+#[test]
+fn three_check_hashing() {
+    use crate::shessboard::rules::Rules;
+    use rand::{rngs::StdRng, seq::IndexedRandom};
+
+    let mut rng = StdRng::from_seed(*BitBoardHasher::PI);
+    let hasher = BitBoardHasher::new();
+    let mut moves = vec![];
+    let mut checks_given = 0;
+    let variant = Variant::ThreeCheck;
+
+    for _ in 0..20 {
+        let mut board = variant.start();
+        let mut hash = variant.hash(&hasher, &board);
+        let mut history = vec![];
+
+        for _ in 0..200 {
+            moves.clear();
+            variant.generate_moves(&board, &mut moves);
+            let Some(&mv) = moves.choose(&mut rng) else {
+                break;
+            };
+
+            hash = variant.hash_delta(&hasher, &board, hash, mv);
+            let before = board.clone();
+            history.push((mv, before, variant.apply(&mut board, mv)));
+            assert_eq!(hash, variant.hash(&hasher, &board));
+
+            if board.metadata.checks(Color::White) >= 3 || board.metadata.checks(Color::Black) >= 3
+            {
+                checks_given += 1;
+                break;
+            }
+        }
+
+        for (mv, before, undo) in history.into_iter().rev() {
+            variant.undo(&mut board, mv, &undo);
+            assert_eq!(board.metadata.white_checks, before.metadata.white_checks);
+            assert_eq!(board.metadata.black_checks, before.metadata.black_checks);
+        }
+    }
+
+    assert!(checks_given > 0);
+}
+
+#[test]
+fn crazyhouse_hashing() {
+    use crate::shessboard::rules::Rules;
+    use rand::{rngs::StdRng, seq::IndexedRandom};
+
+    let mut rng = StdRng::from_seed(*BitBoardHasher::PI);
+    let hasher = BitBoardHasher::new();
+    let mut moves = vec![];
+    let mut drops = 0;
+    let variant = Variant::Crazyhouse;
+
+    for _ in 0..20 {
+        let mut board = variant.start();
+        let mut hash = variant.hash(&hasher, &board);
+
+        for _ in 0..200 {
+            moves.clear();
+            variant.generate_moves(&board, &mut moves);
+            let Some(&mv) = moves.choose(&mut rng) else {
+                break;
+            };
+
+            drops += mv.is_drop() as usize;
+            hash = variant.hash_delta(&hasher, &board, hash, mv);
+            variant.apply(&mut board, mv);
+            assert_eq!(hash, variant.hash(&hasher, &board));
+        }
+    }
+
+    assert!(drops > 0);
+}
+
+#[test]
+fn atomic_and_marseillais_hashing() {
+    use crate::shessboard::rules::Rules;
+    use rand::{rngs::StdRng, seq::IndexedRandom};
+
+    let mut rng = StdRng::from_seed(*BitBoardHasher::PI);
+    let hasher = BitBoardHasher::new();
+    let mut moves = vec![];
+    let mut explosions = 0;
+    let mut second_moves = 0;
+
+    for variant in [Variant::Atomic, Variant::Marseillais] {
+        for _ in 0..20 {
+            let mut board = variant.start();
+            let mut hash = variant.hash(&hasher, &board);
+
+            for _ in 0..200 {
+                moves.clear();
+                variant.generate_moves(&board, &mut moves);
+                let Some(&mv) = moves.choose(&mut rng) else {
+                    break;
+                };
+
+                hash = variant.hash_delta(&hasher, &board, hash, mv);
+                explosions += variant.apply(&mut board, mv).explosion.is_some() as usize;
+                second_moves += board.metadata.sub_turn.second as usize;
+                assert_eq!(hash, variant.hash(&hasher, &board));
+                if !board.white.kings.as_mask().any() || !board.black.kings.as_mask().any() {
+                    break;
+                }
+            }
+        }
+    }
+
+    assert!(explosions > 0);
+    assert!(second_moves > 0);
+}
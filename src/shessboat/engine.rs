@@ -0,0 +1,414 @@
+use crate::shessboard::{
+    BitBoard,
+    forced_draws::{LastChange, ThreefoldRule},
+    moves::Move,
+    pieces::Millipawns,
+    rules::Rules,
+    zobrist::{BitBoardHasher, HashResult},
+};
+
+use super::{
+    MAX_PLY, Minimax, SearchResult,
+    heuristics::evaluation,
+    ordering::MoveOrdering,
+    pv::PvTable,
+    selectivity::Selectivity,
+    time::{Limits, TimeManager},
+    transposition::{Bound, Entry, TranspositionTable},
+};
+
+/// The engine, playing by `R`.
+pub struct Shessboat<R: Rules> {
+    pub rules: R,
+    hasher: BitBoardHasher,
+    pub table: TranspositionTable,
+    pub ordering: MoveOrdering,
+    pub selectivity: Selectivity,
+    pv_table: PvTable,
+    /// Results of every iteration of the last search.
+    pub iterations: Vec<SearchResult>,
+    /// Budgets of the search under way, if it has any.
+    time: Option<TimeManager>,
+    /// Node count when the search under way started.
+    start_nodes: u64,
+    stopped: bool,
+}
+
+impl<R: Rules> Shessboat<R> {
+    pub fn new(rules: R) -> Self {
+        Self {
+            rules,
+            hasher: BitBoardHasher::new(),
+            table: TranspositionTable::new(TranspositionTable::DEFAULT_MB),
+            ordering: MoveOrdering::default(),
+            selectivity: Selectivity::default(),
+            pv_table: PvTable::default(),
+            iterations: Vec::new(),
+            time: None,
+            start_nodes: 0,
+            stopped: false,
+        }
+    }
+
+    /// Searches `board` within `limits`, always finishing the first iteration.
+    pub fn go<'a>(
+        &mut self,
+        board: &BitBoard,
+        limits: &Limits,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> SearchResult {
+        self.time = Some(TimeManager::new(limits, board.metadata.to_move));
+        let res = self.search(board, limits.depth.unwrap_or(MAX_PLY), change, three);
+        self.time = None;
+        res
+    }
+}
+
+impl<R: Rules> Minimax<R> for Shessboat<R> {
+    fn rules(&self) -> &R {
+        &self.rules
+    }
+
+    fn hasher(&self) -> &BitBoardHasher {
+        &self.hasher
+    }
+
+    fn order_moves(
+        &mut self,
+        board: &BitBoard,
+        moves: &mut Vec<Move>,
+        ply: usize,
+        first: Option<Move>,
+        previous: Option<Move>,
+    ) {
+        self.ordering
+            .order(&self.rules, board, moves, ply, first, previous);
+    }
+
+    fn cutoff(
+        &mut self,
+        board: &BitBoard,
+        mv: Move,
+        ply: usize,
+        depth: usize,
+        previous: Option<Move>,
+        index: usize,
+    ) {
+        self.ordering.cutoff(mv, ply, depth, previous, index);
+    }
+
+    fn memorize(
+        &mut self,
+        hash: HashResult,
+        ply: usize,
+        depth: usize,
+        bound: Bound,
+        value: Millipawns,
+        best: Option<Move>,
+    ) {
+        self.table.store(hash, ply, depth, bound, value, best);
+    }
+
+    fn seen_before(&mut self, hash: HashResult, ply: usize) -> Option<Entry> {
+        self.table.probe(hash, ply)
+    }
+
+    fn selectivity(&self) -> Selectivity {
+        self.selectivity
+    }
+
+    fn history(&self, mv: Move) -> Millipawns {
+        self.ordering.history(mv)
+    }
+
+    fn pv_table(&mut self) -> &mut PvTable {
+        &mut self.pv_table
+    }
+
+    fn iteration(&mut self, res: &SearchResult) -> bool {
+        self.iterations.push(res.clone());
+        self.time
+            .as_mut()
+            .is_none_or(|time| time.next_iteration(res))
+    }
+
+    fn should_stop(&mut self) -> bool {
+        if !self.stopped && !self.iterations.is_empty() {
+            let nodes = self.ordering.statistics.nodes - self.start_nodes;
+            self.stopped = self
+                .time
+                .as_ref()
+                .is_some_and(|time| time.out_of_budget(nodes));
+        }
+        self.stopped
+    }
+
+    fn count_node(&mut self) {
+        self.ordering.statistics.nodes += 1;
+    }
+
+    fn new_search(&mut self) {
+        self.table.new_search();
+        self.ordering.new_search();
+        self.iterations.clear();
+        self.start_nodes = self.ordering.statistics.nodes;
+        self.stopped = false;
+    }
+
+    fn static_evaluation(&mut self, board: &BitBoard) -> Millipawns {
+        evaluation(&self.rules, board)
+    }
+}
+
+#[test]
+fn finds_mates() {
+    use crate::shessboard::{
+        enums::{GameEnd, Variant},
+        forced_draws::ThreefoldRule,
+        notation::fen::read_fen,
+        squares::Square,
+    };
+
+    use super::{write_pv, write_score};
+
+    let mut engine = Shessboat::new(Variant::Standard);
+    let three = ThreefoldRule::empty();
+
+    let fen = "r1bqkb1r/pppp1ppp/2n2n2/4p2Q/2B1P3/8/PPPP1PPP/RNB1K1NR w KQkq - 4 4";
+    let ((board, change), "") = read_fen(Variant::Standard, fen).unwrap() else {
+        panic!()
+    };
+    let res = engine.search(&board, 3, &change, &three);
+    assert_eq!(res.best.unwrap().from_to, Square::h5.to(Square::f7));
+    assert_eq!(res.score, GameEnd::VICTORY - 1);
+    assert_eq!(res.pv, vec![res.best.unwrap()]);
+    assert_eq!(write_score(res.score), "#1");
+    assert_eq!(write_pv(&engine.rules, &board, &res.pv), "Qxf7");
+
+    // mated next move whatever happens
+    let fen = "8/8/8/8/1r6/8/2k5/K7 w - - 0 1";
+    let ((board, change), "") = read_fen(Variant::Standard, fen).unwrap() else {
+        panic!()
+    };
+    let res = engine.search(&board, 3, &change, &three);
+    assert_eq!(res.score, GameEnd::DEFEAT + 2);
+    assert_eq!(res.pv.len(), 2);
+    assert_eq!(write_score(res.score), "#-1");
+}
+
+#[test]
+fn wins_material() {
+    use crate::shessboard::{
+        enums::Variant, forced_draws::ThreefoldRule, notation::fen::read_fen, squares::Square,
+    };
+
+    let mut engine = Shessboat::new(Variant::Standard);
+    let three = ThreefoldRule::empty();
+
+    let fen = "4k3/8/8/3q4/8/8/3R4/3K4 w - - 0 1";
+    let ((board, change), "") = read_fen(Variant::Standard, fen).unwrap() else {
+        panic!()
+    };
+    let res = engine.search(&board, 2, &change, &three);
+    assert_eq!(res.best.unwrap().from_to, Square::d2.to(Square::d5));
+    assert!(res.score > 0);
+
+    // the pawn is defended, so taking it loses the queen
+    let fen = "4k3/2p5/3p4/8/8/8/8/3QK3 w - - 0 1";
+    let ((board, change), "") = read_fen(Variant::Standard, fen).unwrap() else {
+        panic!()
+    };
+    let res = engine.search(&board, 2, &change, &three);
+    assert_ne!(res.best.unwrap().from_to, Square::d1.to(Square::d6));
+
+    // which quiescence search sees even at the horizon
+    let res = engine.search(&board, 1, &change, &three);
+    assert_ne!(res.best.unwrap().from_to, Square::d1.to(Square::d6));
+}
+
+#[test]
+fn racing_kings_quiescence() {
+    use crate::shessboard::{
+        enums::{Color, ColorPiece, GameEnd, Variant},
+        forced_draws::{LastChange, ThreefoldRule},
+        squares::Square,
+    };
+
+    use super::is_mate;
+
+    // White has reached the goal, but Black can follow with Kf8 or Kh8, and
+    // quiescence only searches the capture Kxf6
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::RacingKings;
+    board.metadata.to_move = Color::Black;
+    board.set_piece(Some(ColorPiece::WhiteKing), Square::a8);
+    board.set_piece(Some(ColorPiece::WhiteKnight), Square::f6);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::g7);
+
+    let mut engine = Shessboat::new(Variant::RacingKings);
+    let hash = engine.rules.hash(engine.hasher(), &board);
+    let score = engine.quiescence(
+        &board,
+        hash,
+        0,
+        GameEnd::DEFEAT,
+        GameEnd::VICTORY,
+        &LastChange::start(),
+        &ThreefoldRule::empty(),
+    );
+    assert!(!is_mate(score));
+}
+
+#[test]
+fn quiescence_without_exchanges() {
+    use crate::shessboard::{
+        enums::{GameEnd, Variant},
+        notation::fen::read_fen,
+        rules::StandardChess,
+    };
+
+    /// Standard chess, searched as if captures could win whatever they lose.
+    struct Reckless;
+
+    impl Rules for Reckless {
+        fn name(&self) -> &'static str {
+            "reckless"
+        }
+
+        fn read(s: &str) -> Option<(Self, &str)> {
+            Some((Reckless, s.strip_prefix("reckless")?))
+        }
+
+        fn start(&self) -> BitBoard {
+            BitBoard::new()
+        }
+
+        fn material_exchanges_sound(&self) -> bool {
+            false
+        }
+    }
+
+    // the pawn is defended, so taking it loses the queen
+    let fen = "4k3/2p5/3p4/8/8/8/8/3QK3 w - - 0 1";
+    let ((board, change), "") = read_fen(Variant::Standard, fen).unwrap() else {
+        panic!()
+    };
+    let three = ThreefoldRule::empty();
+
+    let mut standard = Shessboat::new(StandardChess);
+    let hash = standard.rules.hash(standard.hasher(), &board);
+    let score = standard.quiescence(
+        &board,
+        hash,
+        0,
+        GameEnd::DEFEAT,
+        GameEnd::VICTORY,
+        &change,
+        &three,
+    );
+
+    // the reckless search plays the exchange out rather than pruning it
+    let mut reckless = Shessboat::new(Reckless);
+    let reckless_score = reckless.quiescence(
+        &board,
+        hash,
+        0,
+        GameEnd::DEFEAT,
+        GameEnd::VICTORY,
+        &change,
+        &three,
+    );
+    assert_eq!(reckless_score, score);
+    assert!(reckless.ordering.statistics.nodes > standard.ordering.statistics.nodes);
+}
+
+#[test]
+fn selective_search() {
+    use crate::shessboard::{
+        enums::Variant,
+        forced_draws::{LastChange, ThreefoldRule},
+        notation::fen::read_fen,
+        squares::Square,
+    };
+
+    let three = ThreefoldRule::empty();
+    let mut nodes = vec![];
+    for selectivity in [Selectivity::NONE, Selectivity::ALL] {
+        let mut engine = Shessboat::new(Variant::Standard);
+        engine.selectivity = selectivity;
+        engine.search(&BitBoard::new(), 5, &LastChange::start(), &three);
+        nodes.push(engine.ordering.statistics.nodes);
+
+        // a knight fork that wins the queen takes three plies to see
+        let fen = "q3k3/8/8/1N6/8/8/8/4K3 w - - 0 1";
+        let ((board, change), "") = read_fen(Variant::Standard, fen).unwrap() else {
+            panic!()
+        };
+        let res = engine.search(&board, 4, &change, &three);
+        assert_eq!(res.best.unwrap().from_to, Square::b5.to(Square::c7));
+    }
+
+    assert!(nodes[1] < nodes[0]);
+}
+
+#[test]
+fn principal_variations() {
+    use crate::shessboard::{
+        enums::Variant,
+        forced_draws::{LastChange, ThreefoldRule},
+    };
+
+    let three = ThreefoldRule::empty();
+    let board = BitBoard::new();
+    let mut scores = vec![];
+
+    for (pvs, aspiration) in [(false, false), (true, false), (true, true)] {
+        let mut engine = Shessboat::new(Variant::Standard);
+        engine.selectivity = Selectivity {
+            principal_variation: pvs,
+            aspiration,
+            ..Selectivity::NONE
+        };
+        let res = engine.search(&board, 4, &LastChange::start(), &three);
+        scores.push(res.score);
+
+        assert_eq!(engine.iterations.len(), 4);
+        assert_eq!(engine.iterations.last(), Some(&res));
+        for (depth, it) in engine.iterations.iter().enumerate() {
+            assert_eq!(it.depth, depth + 1);
+            assert_eq!(it.pv.first(), it.best.as_ref());
+        }
+        assert_eq!(res.pv.len(), 4);
+    }
+
+    assert!(scores.iter().all(|&s| s == scores[0]));
+}
+
+#[test]
+fn limited_search() {
+    use crate::shessboard::enums::Variant;
+
+    let three = ThreefoldRule::empty();
+    let board = BitBoard::new();
+    let mut engine = Shessboat::new(Variant::Standard);
+
+    let res = engine.go(&board, &Limits::depth(3), &LastChange::start(), &three);
+    assert_eq!(res.depth, 3);
+    assert_eq!(engine.iterations.len(), 3);
+
+    let nodes = engine.ordering.statistics.nodes;
+    let (limits, "") = Limits::read("nodes 2000").unwrap() else {
+        panic!()
+    };
+    let res = engine.go(&board, &limits, &LastChange::start(), &three);
+    assert!(res.best.is_some());
+    assert_eq!(engine.iterations.last(), Some(&res));
+    assert!(engine.ordering.statistics.nodes - nodes <= 2000);
+
+    let (limits, "") = Limits::read("movetime 100").unwrap() else {
+        panic!()
+    };
+    let res = engine.go(&board, &limits, &LastChange::start(), &three);
+    assert!(res.best.is_some());
+}
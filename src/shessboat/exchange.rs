@@ -0,0 +1,133 @@
+use crate::shessboard::{
+    BitBoard,
+    enums::{Color, Piece},
+    masks::Mask,
+    moves::Move,
+    pieces::{
+        Millipawns, bishops::Bishops, kings::Kings, knights::Knights, pawns::Pawns, rooks::Rooks,
+    },
+    squares::Square,
+};
+
+/// Pieces of both colors attacking `sq`, with only `occupied` on the board.
+fn attackers(board: &BitBoard, sq: Square, occupied: Mask) -> Mask {
+    let (white, black) = (&board.white, &board.black);
+    let straight = Rooks::new(sq.as_mask()).threats(occupied);
+    let diagonal = Bishops::new(sq.as_mask()).threats(occupied);
+
+    let res = (straight
+        & (white.rooks.as_mask()
+            | white.queens.as_mask()
+            | black.rooks.as_mask()
+            | black.queens.as_mask()))
+        | (diagonal
+            & (white.bishops.as_mask()
+                | white.queens.as_mask()
+                | black.bishops.as_mask()
+                | black.queens.as_mask()))
+        | (Knights::MOVES.at(sq) & (white.knights.as_mask() | black.knights.as_mask()))
+        | (Kings::new(sq.as_mask()).threats() & (white.kings.as_mask() | black.kings.as_mask()))
+        | (Pawns::new(sq.as_mask()).threats(Color::Black) & white.pawns.as_mask())
+        | (Pawns::new(sq.as_mask()).threats(Color::White) & black.pawns.as_mask());
+
+    res & occupied
+}
+
+/// Static exchange evaluation: what the side playing `mv` gains from the
+/// captures that follow on its target square, each side taking with its
+/// least valuable piece and stopping once taking back no longer pays.
+pub fn see(board: &BitBoard, mv: Move) -> Millipawns {
+    let to = mv.from_to.to;
+    let color = mv.color_and_piece.color();
+    let mut occupied = board.as_mask().unset(mv.from_to.from);
+    let mut gain = vec![mv.capture.map_or(0, |(sq, p)| {
+        occupied = occupied.unset(sq);
+        p.materiel()
+    })];
+    let mut on_square = match mv.promotion {
+        Some(p) => {
+            gain[0] += p.materiel() - Piece::Pawn.materiel();
+            p
+        }
+        None => mv.color_and_piece.piece(),
+    };
+
+    let mut side = color.other();
+    loop {
+        let candidates = attackers(board, to, occupied);
+        let half = board.color(side);
+        let Some((piece, from)) = [
+            Piece::Pawn,
+            Piece::Knight,
+            Piece::Bishop,
+            Piece::Rook,
+            Piece::Queen,
+            Piece::King,
+        ]
+        .into_iter()
+        .find_map(|p| Some((p, (half.piece_mask(p) & candidates).first()?))) else {
+            break;
+        };
+
+        // a king may only take when nothing can take it back
+        if piece == Piece::King
+            && (attackers(board, to, occupied.unset(from)) & board.color(side.other()).as_mask())
+                .any()
+        {
+            break;
+        }
+
+        gain.push(on_square.materiel() - gain[gain.len() - 1]);
+        occupied = occupied.unset(from);
+        on_square = piece;
+        side = side.other();
+    }
+
+    while gain.len() > 1 {
+        let last = gain.pop().unwrap();
+        let prev = gain.last_mut().unwrap();
+        *prev = -(-*prev).max(last);
+    }
+    gain[0]
+}
+
+#[test]
+fn exchanges() {
+    use crate::shessboard::{enums::Variant, notation::fen::read_fen};
+
+    let find = |fen: &str, from: Square, to: Square| {
+        let ((board, _), "") = read_fen(Variant::Standard, fen).unwrap() else {
+            panic!()
+        };
+        let mut moves = vec![];
+        board.generate_moves(&mut moves);
+        let mv = *moves.iter().find(|mv| mv.from_to == from.to(to)).unwrap();
+        see(&board, mv)
+    };
+
+    // an undefended pawn
+    let fen = "4k3/8/8/3p4/8/8/8/3QK3 w - - 0 1";
+    assert_eq!(find(fen, Square::d1, Square::d5), Piece::Pawn.materiel());
+
+    // a pawn defended by a pawn
+    let fen = "4k3/8/4p3/3p4/8/8/8/3QK3 w - - 0 1";
+    assert_eq!(
+        find(fen, Square::d1, Square::d5),
+        Piece::Pawn.materiel() - Piece::Queen.materiel()
+    );
+
+    // the queen behind the rook joins in once the rook has taken
+    let fen = "3rk3/8/8/3p4/8/8/3R4/3QK3 w - - 0 1";
+    assert_eq!(find(fen, Square::d2, Square::d5), Piece::Pawn.materiel());
+    let fen = "3rk3/8/8/3p4/8/8/3R4/4K3 w - - 0 1";
+    assert_eq!(
+        find(fen, Square::d2, Square::d5),
+        Piece::Pawn.materiel() - Piece::Rook.materiel()
+    );
+
+    // a quiet move onto an attacked square
+    let fen = "4k3/8/4p3/8/8/8/8/3NK3 w - - 0 1";
+    assert_eq!(find(fen, Square::d1, Square::c3), 0);
+    let fen = "4k3/8/4p3/8/8/2N5/8/4K3 w - - 0 1";
+    assert_eq!(find(fen, Square::c3, Square::d5), -Piece::Knight.materiel());
+}
@@ -0,0 +1,110 @@
+use rand::{Rng, seq::IndexedRandom};
+
+use crate::shessboard::{
+    BitBoard,
+    enums::{GameEnd, Piece},
+    moves::Move,
+    pieces::Millipawns,
+    rules::Rules,
+    variants::FogOfWar,
+};
+
+/// Picks a fog of war move from what the side to move can see. It takes the
+/// king whenever it is visible, prefers valuable captures, and avoids
+/// leaving pieces on squares attacked by visible enemies.
+pub fn fog_move<R: Rng + ?Sized>(board: &BitBoard, rng: &mut R) -> Option<Move> {
+    let view = board.fogged(board.metadata.to_move);
+    let mut moves = Vec::with_capacity(50);
+    FogOfWar.generate_moves(board, &mut moves);
+
+    let scored = moves
+        .into_iter()
+        .map(|mv| (score(&view, mv), mv))
+        .collect::<Vec<_>>();
+    let best = scored.iter().map(|(s, _)| *s).max()?;
+
+    scored
+        .into_iter()
+        .filter(|(s, _)| *s == best)
+        .map(|(_, mv)| mv)
+        .collect::<Vec<_>>()
+        .choose(rng)
+        .copied()
+}
+
+fn score(view: &BitBoard, mv: Move) -> Millipawns {
+    let color = mv.color_and_piece.color();
+    let mut res = match mv.capture {
+        Some((_, Piece::King)) => return GameEnd::VICTORY,
+        Some((_, p)) => p.materiel(),
+        None => 0,
+    };
+
+    let mut after = view.clone();
+    FogOfWar.apply(&mut after, mv);
+    let own = after.color(color);
+    let threats = after
+        .color(color.other())
+        .threats(color.other(), own.as_mask(), None);
+
+    if (threats & own.kings.as_mask()).any() {
+        res -= GameEnd::VICTORY / 2;
+    }
+    if threats.contains(mv.from_to.to) {
+        res -= mv
+            .promotion
+            .unwrap_or(mv.color_and_piece.piece())
+            .materiel();
+    }
+
+    res
+}
+
+#[test]
+fn fog_engine_games() {
+    use crate::shessboard::{
+        enums::{ColorPiece, Variant},
+        forced_draws::{LastChange, ThreefoldRule},
+        squares::Square,
+    };
+    use rand::{SeedableRng, rngs::StdRng};
+
+    let mut rng = StdRng::from_seed(*b"3.141592653589793238462643383279");
+
+    let mut board = BitBoard::empty();
+    board.metadata.variant = Variant::FogOfWar;
+    board.set_piece(Some(ColorPiece::WhiteKing), Square::a1);
+    board.set_piece(Some(ColorPiece::WhiteRook), Square::e2);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::e7);
+    board.set_piece(Some(ColorPiece::BlackQueen), Square::b2);
+    let mv = fog_move(&board, &mut rng).unwrap();
+    assert_eq!(mv.capture, Some((Square::e7, Piece::King)));
+
+    board.set_piece(None, Square::e7);
+    board.set_piece(Some(ColorPiece::BlackKing), Square::h8);
+    let mv = fog_move(&board, &mut rng).unwrap();
+    assert_eq!(mv.capture, Some((Square::b2, Piece::Queen)));
+
+    let mut moves = vec![];
+    let mut decisive = 0;
+    for _ in 0..10 {
+        let mut board = FogOfWar.start();
+        for _ in 0..300 {
+            moves.clear();
+            FogOfWar.generate_moves(&board, &mut moves);
+            if let Some(end) = FogOfWar.game_end(
+                &board,
+                &moves,
+                0,
+                &LastChange::start(),
+                &ThreefoldRule::empty(),
+            ) {
+                decisive += (end != GameEnd::Draw) as usize;
+                break;
+            }
+            let mv = fog_move(&board, &mut rng).unwrap();
+            FogOfWar.apply(&mut board, mv);
+        }
+    }
+    assert!(decisive > 0);
+}
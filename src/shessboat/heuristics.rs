@@ -1,6 +1,9 @@
 use crate::shessboard::{
+    BitBoard,
     boardmap::BoardMap,
+    enums::{Color, Piece},
     pieces::{Millipawns, P},
+    rules::Rules,
 };
 
 const OPENING_INCENTIVE: (BoardMap<Millipawns>, BoardMap<Millipawns>) = {
@@ -12,7 +15,7 @@ const OPENING_INCENTIVE: (BoardMap<Millipawns>, BoardMap<Millipawns>) = {
         [0; 8],
         [0; 8],
         [0; 8],
-        [0, -1 * P, -1 * P, -1 * P, 0, -1 * P, -1 * P, 0],
+        [0, -P, -P, -P, 0, -P, -P, 0],
     ])
 };
 
@@ -23,7 +26,7 @@ const PAWN_POSITION: (BoardMap<Millipawns>, BoardMap<Millipawns>) = {
         [5 * P / 10; 8],
         [3 * P / 10; 8],
         [2 * P / 10; 8],
-        [1 * P / 10; 8],
+        [P / 10; 8],
         [0; 8],
         [0; 8],
     ])
@@ -34,3 +37,50 @@ const KING_SAFETY_INCENTIVE: (BoardMap<Millipawns>, BoardMap<Millipawns>) = {
         [0; 8], [0; 8], [0; 8], [0; 8], [0; 8], [0; 8], [0; 8], [0; 8],
     ])
 };
+
+/// Evaluation of `board` under `rules`, from the point of view of the side
+/// to move.
+pub fn evaluation<R: Rules + ?Sized>(rules: &R, board: &BitBoard) -> Millipawns {
+    let color = board.metadata.to_move;
+    side_evaluation(rules, board, color) - side_evaluation(rules, board, color.other())
+}
+
+fn side_evaluation<R: Rules + ?Sized>(rules: &R, board: &BitBoard, color: Color) -> Millipawns {
+    let half = board.color(color);
+    let table = |tables: &'static (BoardMap<Millipawns>, BoardMap<Millipawns>)| match color {
+        Color::White => &tables.0,
+        Color::Black => &tables.1,
+    };
+
+    let mut res = rules.materiel(half);
+    res += table(&PAWN_POSITION).sum_mask(half.pawns.as_mask());
+    res += table(&OPENING_INCENTIVE).sum_mask(half.knights.as_mask() | half.bishops.as_mask());
+    res += table(&KING_SAFETY_INCENTIVE).sum_mask(half.kings.as_mask());
+    res += rules.goal_incentive(board, color);
+
+    res
+}
+
+#[test]
+fn symmetric_evaluation() {
+    use crate::shessboard::{
+        enums::ColorPiece,
+        rules::StandardChess,
+        squares::Square,
+        variants::{Antichess, KingOfTheHill},
+    };
+
+    let mut board = BitBoard::new();
+    assert_eq!(evaluation(&StandardChess, &board), 0);
+
+    board.set_piece(None, Square::d8);
+    assert!(evaluation(&StandardChess, &board) > Piece::Queen.materiel() / 2);
+    assert!(evaluation(&Antichess, &board) < -Piece::Queen.materiel() / 2);
+    board.metadata.to_move = Color::Black;
+    assert!(evaluation(&StandardChess, &board) < -Piece::Queen.materiel() / 2);
+
+    // only on the hill does the white king count for more up the board
+    board.set_piece(None, Square::e1);
+    board.set_piece(Some(ColorPiece::WhiteKing), Square::e4);
+    assert!(evaluation(&KingOfTheHill, &board) < evaluation(&StandardChess, &board));
+}
@@ -1,19 +1,597 @@
 use std::hash::Hash;
 
+pub mod engine;
+pub mod exchange;
+pub mod fog;
 mod heuristics;
+pub mod ordering;
+pub mod pv;
+pub mod selectivity;
+pub mod time;
+pub mod transposition;
 
 use crate::shessboard::{
     BitBoard,
     boardmap::BoardMap,
+    enums::{GameEnd, Piece, Variant},
     forced_draws::{LastChange, ThreefoldRule},
     moves::Move,
     pieces::{Millipawns, P},
+    rules::Rules,
     zobrist::{BitBoardHasher, HashResult},
 };
 
-trait Minimax {
-    fn order_moves(&mut self, moves: &mut Vec<Move>);
-    fn memorize(&mut self, hash: HashResult, value: Millipawns);
-    fn seen_before(&mut self, hash: HashResult) -> Option<Millipawns>;
+use exchange::see;
+use pv::PvTable;
+use selectivity::{
+    FRONTIER, FUTILITY_MARGIN, Selectivity, late_move_count, late_move_reduction, null_move,
+    null_move_allowed, null_move_reduction,
+};
+use transposition::{Bound, Entry};
+
+/// What a search settled on after its deepest finished iteration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SearchResult {
+    pub best: Option<Move>,
+    /// From the point of view of the side to move. Won and lost games are
+    /// `GameEnd::VICTORY` and `GameEnd::DEFEAT`, less the plies to the end.
+    pub score: Millipawns,
+    pub pv: Vec<Move>,
+    pub depth: usize,
+}
+
+/// Whether `score` is a forced win or loss rather than an evaluation.
+pub fn is_mate(score: Millipawns) -> bool {
+    score.abs() > GameEnd::VICTORY / 2
+}
+
+/// Plies until the game ends, for scores where `is_mate` holds.
+pub fn mate_distance(score: Millipawns) -> usize {
+    (GameEnd::VICTORY - score.abs()) as usize
+}
+
+/// How far past the horizon quiescence search may go.
+const MAX_PLY: usize = 64;
+
+/// Half the width of the first aspiration window.
+const ASPIRATION_WINDOW: Millipawns = P / 2;
+
+/// Room left in delta pruning for positional gains on top of the materiel.
+const DELTA_MARGIN: Millipawns = 2 * P;
+
+/// The score of a finished game for the side to move, `ply` plies in.
+fn end_score(end: GameEnd, board: &BitBoard, ply: usize) -> Millipawns {
+    match end.value(board.metadata.to_move) {
+        GameEnd::VICTORY => GameEnd::VICTORY - ply as Millipawns,
+        GameEnd::DEFEAT => GameEnd::DEFEAT + ply as Millipawns,
+        draw => draw,
+    }
+}
+
+/// Scores as pawns, or as `#n` and `#-n` for mates in `n` moves.
+pub fn write_score(score: Millipawns) -> String {
+    if is_mate(score) {
+        let moves = mate_distance(score).div_ceil(2);
+        format!("#{}{moves}", if score < 0 { "-" } else { "" })
+    } else {
+        format!("{:+.2}", score as f64 / P as f64)
+    }
+}
+
+/// The moves of `pv` in algebraic notation, played out from `board`.
+pub fn write_pv<R: Rules>(rules: &R, board: &BitBoard, pv: &[Move]) -> String {
+    let mut board = board.clone();
+    let mut moves = Vec::with_capacity(50);
+    let mut res = Vec::with_capacity(pv.len());
+
+    for &mv in pv {
+        moves.clear();
+        rules.generate_moves(&board, &mut moves);
+        res.push(rules.notation(&mv, &moves).to_string());
+        rules.apply(&mut board, mv);
+    }
+
+    res.join(" ")
+}
+
+/// A search that plays by whatever rules it is given.
+pub trait Minimax<R: Rules> {
+    fn rules(&self) -> &R;
+    fn hasher(&self) -> &BitBoardHasher;
+    /// Sorts `moves` so the likeliest to cut off come first, starting with
+    /// `first` when it is among them. `previous` led to `board`.
+    fn order_moves(
+        &mut self,
+        board: &BitBoard,
+        moves: &mut Vec<Move>,
+        ply: usize,
+        first: Option<Move>,
+        previous: Option<Move>,
+    );
+    /// Remembers what searching `hash` to `depth` found, `ply` plies from
+    /// the root.
+    fn memorize(
+        &mut self,
+        hash: HashResult,
+        ply: usize,
+        depth: usize,
+        bound: Bound,
+        value: Millipawns,
+        best: Option<Move>,
+    );
+    fn seen_before(&mut self, hash: HashResult, ply: usize) -> Option<Entry>;
     fn static_evaluation(&mut self, board: &BitBoard) -> Millipawns;
+
+    /// Called once before every search.
+    fn new_search(&mut self) {}
+
+    fn pv_table(&mut self) -> &mut PvTable;
+
+    /// Called with the result of every finished iteration, telling whether
+    /// to start another.
+    fn iteration(&mut self, res: &SearchResult) -> bool {
+        true
+    }
+
+    /// Polled at every node. Once it holds, the iteration under way is
+    /// abandoned and the search settles on the last finished one.
+    fn should_stop(&mut self) -> bool {
+        false
+    }
+
+    /// Which search techniques to use.
+    fn selectivity(&self) -> Selectivity {
+        Selectivity::NONE
+    }
+
+    /// How well `mv` has done in earlier cutoffs, for late move reductions.
+    fn history(&self, mv: Move) -> Millipawns {
+        0
+    }
+
+    /// Called for every node searched, quiescence included.
+    fn count_node(&mut self) {}
+
+    /// Called when `mv`, the `index`th move tried in `board`, failed high.
+    fn cutoff(
+        &mut self,
+        board: &BitBoard,
+        mv: Move,
+        ply: usize,
+        depth: usize,
+        previous: Option<Move>,
+        index: usize,
+    ) {
+    }
+
+    /// Searches one ply deeper at a time up to `depth`, or until told to
+    /// stop. From the second iteration on, the search starts in a window
+    /// around the previous score that widens every time the score falls
+    /// outside it.
+    fn search<'a>(
+        &mut self,
+        board: &BitBoard,
+        depth: usize,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> SearchResult {
+        self.new_search();
+        self.pv_table().clear(0);
+        let hash = self.rules().hash(self.hasher(), board);
+        let mut res = SearchResult {
+            best: None,
+            score: self.static_evaluation(board),
+            pv: Vec::new(),
+            depth: 0,
+        };
+
+        for depth in 1..=depth.clamp(1, MAX_PLY - 1) {
+            let aspiration = self.selectivity().aspiration && depth > 1 && !is_mate(res.score);
+            let mut window = ASPIRATION_WINDOW;
+            let (mut alpha, mut beta) = if aspiration {
+                (res.score - window, res.score + window)
+            } else {
+                (GameEnd::DEFEAT, GameEnd::VICTORY)
+            };
+
+            let score = loop {
+                let score = self.negamax(board, hash, depth, 0, None, alpha, beta, change, three);
+                if self.should_stop() {
+                    return res;
+                }
+                if score <= alpha && alpha > GameEnd::DEFEAT {
+                    alpha = (score - window).max(GameEnd::DEFEAT);
+                } else if score >= beta && beta < GameEnd::VICTORY {
+                    beta = (score + window).min(GameEnd::VICTORY);
+                } else {
+                    break score;
+                }
+                window *= 2;
+            };
+
+            let pv = self.pv_table().line().to_vec();
+            res = SearchResult {
+                best: pv.first().copied(),
+                score,
+                pv,
+                depth,
+            };
+            if !self.iteration(&res) || is_mate(score) && mate_distance(score) <= depth {
+                break;
+            }
+        }
+
+        res
+    }
+
+    /// Alpha-beta negamax, leaving the principal variation from `board` in
+    /// the row of `ply` in the PV table. With principal variation search on,
+    /// every move after the first is searched with a zero window first.
+    #[allow(clippy::too_many_arguments)]
+    fn negamax<'a>(
+        &mut self,
+        board: &BitBoard,
+        hash: HashResult,
+        depth: usize,
+        ply: usize,
+        previous: Option<Move>,
+        mut alpha: Millipawns,
+        beta: Millipawns,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Millipawns {
+        // the root keeps the variation of the previous iteration until a
+        // move beats alpha, and tries its first move first
+        let hint = if ply == 0 {
+            self.pv_table().line().first().copied()
+        } else {
+            self.pv_table().clear(ply);
+            None
+        };
+        if depth == 0 {
+            return self.quiescence(board, hash, ply, alpha, beta, change, three);
+        }
+        if self.should_stop() {
+            return 0;
+        }
+        self.count_node();
+
+        let mut moves = Vec::with_capacity(50);
+        self.rules().generate_moves(board, &mut moves);
+
+        if let Some(end) = self.rules().game_end(board, &moves, hash, change, three) {
+            return end_score(end, board, ply);
+        }
+
+        let seen = self.seen_before(hash, ply);
+        if let Some(entry) = seen
+            && ply > 0
+            && entry.depth as usize >= depth
+            && match entry.bound {
+                Bound::Exact => true,
+                Bound::Lower => entry.score >= beta,
+                Bound::Upper => entry.score <= alpha,
+            }
+        {
+            return entry.score;
+        }
+
+        let color = board.metadata.to_move;
+        let selectivity = self.selectivity();
+        let in_check = self.rules().is_in_check(board, color);
+        let eval = self.static_evaluation(board);
+        let selective = ply > 0 && !in_check && !is_mate(alpha) && !is_mate(beta);
+
+        if selective
+            && selectivity.reverse_futility
+            && depth <= FRONTIER
+            && eval - FUTILITY_MARGIN * depth as Millipawns >= beta
+        {
+            return eval;
+        }
+
+        // the child of a null move has no previous move, so passing twice
+        // in a row is ruled out
+        if selective
+            && selectivity.null_move
+            && depth >= 3
+            && previous.is_some()
+            && eval >= beta
+            && null_move_allowed(self.rules(), board, color)
+        {
+            let child = null_move(board);
+            let child_hash = self.rules().hash(self.hasher(), &child);
+            let child_three = three.see(child_hash);
+            let reduced = depth.saturating_sub(1 + null_move_reduction(depth));
+            let value = -self.negamax(
+                &child,
+                child_hash,
+                reduced,
+                ply + 1,
+                None,
+                -beta,
+                -beta + 1,
+                change,
+                &child_three,
+            );
+            if value >= beta {
+                return if is_mate(value) { beta } else { value };
+            }
+        }
+
+        let first = hint.or(seen.and_then(|e| e.best));
+        self.order_moves(board, &mut moves, ply, first, previous);
+
+        let futile = selective
+            && selectivity.futility
+            && depth <= FRONTIER
+            && eval + FUTILITY_MARGIN * depth as Millipawns <= alpha;
+        let original_alpha = alpha;
+        let mut best = GameEnd::DEFEAT;
+        let mut best_move = None;
+        let mut quiets = 0;
+
+        for (i, mv) in moves.into_iter().enumerate() {
+            let quiet = mv.capture.is_none() && mv.promotion.is_none();
+            if quiet && i > 0 && selective && depth <= FRONTIER {
+                quiets += 1;
+                let late = selectivity.late_move_pruning && quiets > late_move_count(depth);
+                if (futile || late) && !self.rules().gives_check(board, mv) {
+                    continue;
+                }
+            }
+
+            let child_hash = self.rules().hash_delta(self.hasher(), board, hash, mv);
+            let mut child = board.clone();
+            self.rules().apply(&mut child, mv);
+            let child_change = change.see(child.metadata.tempo, mv);
+            let child_three = three.see(child_hash);
+
+            let reduction = if selectivity.late_move_reductions
+                && quiet
+                && selective
+                && !self.rules().is_in_check(&child, child.metadata.to_move)
+            {
+                late_move_reduction(depth, i, self.history(mv))
+            } else {
+                0
+            };
+            let zero_window = selectivity.principal_variation && i > 0;
+
+            // a reduced or zero window search that beats alpha has to be
+            // confirmed at full depth and with the full window
+            let mut value = best;
+            let mut full = true;
+            if reduction > 0 {
+                value = self.child_score(
+                    board,
+                    &child,
+                    child_hash,
+                    depth - 1 - reduction,
+                    ply,
+                    mv,
+                    alpha,
+                    alpha + 1,
+                    &child_change,
+                    &child_three,
+                );
+                full = value > alpha;
+            }
+            if full && zero_window {
+                value = self.child_score(
+                    board,
+                    &child,
+                    child_hash,
+                    depth - 1,
+                    ply,
+                    mv,
+                    alpha,
+                    alpha + 1,
+                    &child_change,
+                    &child_three,
+                );
+                full = value > alpha && value < beta;
+            }
+            if full {
+                value = self.child_score(
+                    board,
+                    &child,
+                    child_hash,
+                    depth - 1,
+                    ply,
+                    mv,
+                    alpha,
+                    beta,
+                    &child_change,
+                    &child_three,
+                );
+            }
+            if self.should_stop() {
+                return 0;
+            }
+
+            if value > best || best_move.is_none() {
+                best = value;
+                best_move = Some(mv);
+            }
+            if value > alpha {
+                alpha = value;
+                self.pv_table().update(ply, mv);
+            }
+
+            if alpha >= beta {
+                self.cutoff(board, mv, ply, depth, previous, i);
+                break;
+            }
+        }
+
+        let bound = if best <= original_alpha {
+            Bound::Upper
+        } else if best >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        let best_move = best_move.filter(|_| bound != Bound::Upper);
+        self.memorize(hash, ply, depth, bound, best, best_move);
+
+        best
+    }
+
+    /// Searches `child`, reached by `mv` from `board`, and scores it for the
+    /// side to move in `board`. In marseillais that may well be the side to
+    /// move in `child` too.
+    #[allow(clippy::too_many_arguments)]
+    fn child_score<'a>(
+        &mut self,
+        board: &BitBoard,
+        child: &BitBoard,
+        child_hash: HashResult,
+        depth: usize,
+        ply: usize,
+        mv: Move,
+        alpha: Millipawns,
+        beta: Millipawns,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Millipawns {
+        if child.metadata.to_move == board.metadata.to_move {
+            self.negamax(
+                child,
+                child_hash,
+                depth,
+                ply + 1,
+                Some(mv),
+                alpha,
+                beta,
+                change,
+                three,
+            )
+        } else {
+            -self.negamax(
+                child,
+                child_hash,
+                depth,
+                ply + 1,
+                Some(mv),
+                -beta,
+                -alpha,
+                change,
+                three,
+            )
+        }
+    }
+
+    /// Searches captures and queen promotions past the horizon until the
+    /// position is quiet, or every reply when in check. Otherwise the side to
+    /// move may stand pat on the static evaluation. Captures that cannot
+    /// raise alpha even with a margin, or that lose materiel in the exchange,
+    /// are skipped.
+    #[allow(clippy::too_many_arguments)]
+    fn quiescence<'a>(
+        &mut self,
+        board: &BitBoard,
+        hash: HashResult,
+        ply: usize,
+        mut alpha: Millipawns,
+        beta: Millipawns,
+        change: &'a LastChange<'a>,
+        three: &'a ThreefoldRule<'a>,
+    ) -> Millipawns {
+        if self.should_stop() {
+            return 0;
+        }
+        self.count_node();
+        let color = board.metadata.to_move;
+        let in_check = self.rules().is_in_check(board, color);
+        let mut moves = Vec::with_capacity(50);
+        if in_check {
+            self.rules().generate_moves(board, &mut moves);
+        } else {
+            self.rules().generate_captures(board, &mut moves);
+        }
+
+        // the end only asks whether any move is left, which captures answer
+        // unless there are none
+        let end = if in_check || !moves.is_empty() {
+            self.rules().game_end(board, &moves, hash, change, three)
+        } else {
+            let mut all = Vec::with_capacity(50);
+            self.rules().generate_moves(board, &mut all);
+            self.rules().game_end(board, &all, hash, change, three)
+        };
+        if let Some(end) = end {
+            return end_score(end, board, ply);
+        }
+
+        let stand_pat = self.static_evaluation(board);
+        if ply >= MAX_PLY {
+            return stand_pat;
+        }
+        let mut best = if in_check {
+            GameEnd::DEFEAT + ply as Millipawns
+        } else {
+            stand_pat
+        };
+        if best >= beta {
+            return best;
+        }
+        alpha = alpha.max(best);
+
+        // material gained is no guide unless the rules make exchanges sound
+        let prune = !in_check && self.rules().material_exchanges_sound();
+
+        self.order_moves(board, &mut moves, ply, None, None);
+        for mv in moves {
+            if prune {
+                let gain = mv.capture.map_or(0, |(_, p)| p.materiel())
+                    + mv.promotion
+                        .map_or(0, |p| p.materiel() - Piece::Pawn.materiel());
+                if stand_pat + gain + DELTA_MARGIN <= alpha || see(board, mv) < 0 {
+                    continue;
+                }
+            }
+
+            let child_hash = self.rules().hash_delta(self.hasher(), board, hash, mv);
+            let mut child = board.clone();
+            self.rules().apply(&mut child, mv);
+            let child_change = change.see(child.metadata.tempo, mv);
+            let child_three = three.see(child_hash);
+
+            let value = if child.metadata.to_move == color {
+                self.quiescence(
+                    &child,
+                    child_hash,
+                    ply + 1,
+                    alpha,
+                    beta,
+                    &child_change,
+                    &child_three,
+                )
+            } else {
+                -self.quiescence(
+                    &child,
+                    child_hash,
+                    ply + 1,
+                    -beta,
+                    -alpha,
+                    &child_change,
+                    &child_three,
+                )
+            };
+            if self.should_stop() {
+                return 0;
+            }
+
+            if value > best {
+                best = value;
+                alpha = alpha.max(value);
+            }
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best
+    }
 }
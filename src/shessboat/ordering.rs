@@ -0,0 +1,217 @@
+use std::{cmp::Reverse, fmt::Display};
+
+use crate::shessboard::{BitBoard, moves::Move, pieces::Millipawns, rules::Rules};
+
+use super::{MAX_PLY, exchange::see};
+
+const FIRST: Millipawns = 1 << 50;
+const WINNING_CAPTURE: Millipawns = 1 << 40;
+const KILLER: Millipawns = 1 << 30;
+const COUNTER: Millipawns = KILLER - 10;
+/// History scores are halved once one reaches this, so that they stay
+/// below the killers and follow what the search currently finds.
+const HISTORY_LIMIT: Millipawns = 1 << 20;
+const LOSING_CAPTURE: Millipawns = -(1 << 40);
+
+/// Piece and target square of a move.
+type PieceTo<T> = [[T; 64]; 13];
+
+fn piece_to(mv: Move) -> (usize, usize) {
+    (mv.color_and_piece as usize, mv.from_to.to.index() as usize)
+}
+
+fn is_quiet(mv: Move) -> bool {
+    mv.capture.is_none() && mv.promotion.is_none()
+}
+
+/// How often the moves tried first were good enough for a cutoff.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    pub nodes: u64,
+    pub cutoffs: u64,
+    pub first_move_cutoffs: u64,
+}
+
+impl Statistics {
+    /// Share of cutoffs caused by the first move tried.
+    pub fn first_move_rate(&self) -> f64 {
+        if self.cutoffs == 0 {
+            0.0
+        } else {
+            self.first_move_cutoffs as f64 / self.cutoffs as f64
+        }
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} nodes, {} cutoffs, {:.1}% on the first move",
+            self.nodes,
+            self.cutoffs,
+            100.0 * self.first_move_rate()
+        )
+    }
+}
+
+/// Orders the transposition table move first, then captures that win or
+/// hold materiel by MVV-LVA, the killer moves of the ply, the countermove
+/// to the previous move, other quiet moves by history, and finally captures
+/// that lose materiel.
+pub struct MoveOrdering {
+    killers: Vec<[Option<Move>; 2]>,
+    countermoves: Box<PieceTo<Option<Move>>>,
+    history: Box<PieceTo<Millipawns>>,
+    pub statistics: Statistics,
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        Self {
+            killers: vec![[None; 2]; MAX_PLY + 1],
+            countermoves: Box::new([[None; 64]; 13]),
+            history: Box::new([[0; 64]; 13]),
+            statistics: Statistics::default(),
+        }
+    }
+}
+
+impl MoveOrdering {
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Killers belong to a single search, statistics are kept until cleared.
+    pub fn new_search(&mut self) {
+        self.killers.fill([None; 2]);
+    }
+
+    pub fn order<R: Rules + ?Sized>(
+        &self,
+        rules: &R,
+        board: &BitBoard,
+        moves: &mut [Move],
+        ply: usize,
+        first: Option<Move>,
+        previous: Option<Move>,
+    ) {
+        let killers = self.killers[ply.min(MAX_PLY)];
+        let counter = previous.and_then(|prev| {
+            let (p, to) = piece_to(prev);
+            self.countermoves[p][to]
+        });
+        let exchanges = rules.material_exchanges_sound();
+
+        moves.sort_by_cached_key(|&mv| {
+            Reverse(if Some(mv) == first {
+                FIRST
+            } else if !is_quiet(mv) {
+                let victim = mv.capture.map_or(0, |(_, p)| p.materiel())
+                    + mv.promotion.map_or(0, |p| p.materiel());
+                let mvv_lva = 16 * victim - mv.color_and_piece.piece().materiel();
+                if !exchanges || see(board, mv) >= 0 {
+                    WINNING_CAPTURE + mvv_lva
+                } else {
+                    LOSING_CAPTURE + mvv_lva
+                }
+            } else if let Some(i) = killers.iter().position(|&k| k == Some(mv)) {
+                KILLER - i as Millipawns
+            } else if Some(mv) == counter {
+                COUNTER
+            } else {
+                let (p, to) = piece_to(mv);
+                self.history[p][to]
+            })
+        });
+    }
+
+    pub fn history(&self, mv: Move) -> Millipawns {
+        let (p, to) = piece_to(mv);
+        self.history[p][to]
+    }
+
+    pub fn cutoff(
+        &mut self,
+        mv: Move,
+        ply: usize,
+        depth: usize,
+        previous: Option<Move>,
+        index: usize,
+    ) {
+        self.statistics.cutoffs += 1;
+        if index == 0 {
+            self.statistics.first_move_cutoffs += 1;
+        }
+        if !is_quiet(mv) {
+            return;
+        }
+
+        let killers = &mut self.killers[ply.min(MAX_PLY)];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+
+        if let Some(prev) = previous {
+            let (p, to) = piece_to(prev);
+            self.countermoves[p][to] = Some(mv);
+        }
+
+        let (p, to) = piece_to(mv);
+        self.history[p][to] += (depth * depth) as Millipawns;
+        if self.history[p][to] >= HISTORY_LIMIT {
+            for row in self.history.iter_mut() {
+                for h in row.iter_mut() {
+                    *h /= 2;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn move_order() {
+    use crate::shessboard::{
+        enums::Variant, notation::fen::read_fen, rules::StandardChess, squares::Square,
+        variants::Antichess,
+    };
+
+    let fen = "4k3/3p4/2p5/8/1n6/2Q5/8/4K1N1 w - - 0 1";
+    let ((board, _), "") = read_fen(Variant::Standard, fen).unwrap() else {
+        panic!()
+    };
+    let mut moves = vec![];
+    board.generate_moves(&mut moves);
+    let find =
+        |from: Square, to: Square| *moves.iter().find(|mv| mv.from_to == from.to(to)).unwrap();
+    let qxb4 = find(Square::c3, Square::b4);
+    let qxc6 = find(Square::c3, Square::c6);
+    let nf3 = find(Square::g1, Square::f3);
+    let ne2 = find(Square::g1, Square::e2);
+    let kd1 = find(Square::e1, Square::d1);
+
+    let mut ordering = MoveOrdering::default();
+    ordering.order(&StandardChess, &board, &mut moves, 1, None, None);
+    assert_eq!(moves[0], qxb4);
+    assert_eq!(*moves.last().unwrap(), qxc6);
+
+    // no capture loses by exchanges when material is no guide
+    ordering.order(&Antichess, &board, &mut moves, 1, None, None);
+    assert_eq!(moves[..2], [qxb4, qxc6]);
+
+    ordering.order(&StandardChess, &board, &mut moves, 1, Some(kd1), None);
+    assert_eq!(moves[..2], [kd1, qxb4]);
+
+    ordering.cutoff(nf3, 1, 3, None, 4);
+    ordering.cutoff(ne2, 2, 3, Some(qxb4), 0);
+    ordering.order(&StandardChess, &board, &mut moves, 1, None, Some(qxb4));
+    assert_eq!(moves[..3], [qxb4, nf3, ne2]);
+
+    // only the history is left for another ply and previous move
+    ordering.order(&StandardChess, &board, &mut moves, 3, None, None);
+    assert!(moves[1] == nf3 || moves[1] == ne2);
+
+    assert_eq!(ordering.statistics.cutoffs, 2);
+    assert_eq!(ordering.statistics.first_move_rate(), 0.5);
+}
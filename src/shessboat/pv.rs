@@ -0,0 +1,69 @@
+use crate::shessboard::moves::Move;
+
+use super::MAX_PLY;
+
+/// Triangular table of principal variations, one row per ply. The row of a
+/// ply is its best move followed by the row of the ply below.
+pub struct PvTable {
+    rows: Vec<Vec<Move>>,
+}
+
+impl Default for PvTable {
+    fn default() -> Self {
+        Self {
+            rows: (0..=MAX_PLY)
+                .map(|n| Vec::with_capacity(MAX_PLY - n))
+                .collect(),
+        }
+    }
+}
+
+impl PvTable {
+    /// Forgets the variation from `ply`, as when a node is entered.
+    pub fn clear(&mut self, ply: usize) {
+        if let Some(row) = self.rows.get_mut(ply) {
+            row.clear();
+        }
+    }
+
+    /// Makes `mv` followed by the variation below the variation from `ply`.
+    pub fn update(&mut self, ply: usize, mv: Move) {
+        if ply >= MAX_PLY {
+            return;
+        }
+        let (above, below) = self.rows.split_at_mut(ply + 1);
+        let row = &mut above[ply];
+        row.clear();
+        row.push(mv);
+        row.extend_from_slice(&below[0]);
+    }
+
+    /// The principal variation from the root.
+    pub fn line(&self) -> &[Move] {
+        &self.rows[0]
+    }
+}
+
+#[test]
+fn triangular_updates() {
+    use crate::shessboard::BitBoard;
+
+    let mut moves = vec![];
+    BitBoard::new().generate_moves(&mut moves);
+    let mut table = PvTable::default();
+
+    table.clear(2);
+    table.update(2, moves[2]);
+    table.update(1, moves[1]);
+    table.clear(2);
+    table.update(0, moves[0]);
+    assert_eq!(table.line(), &moves[..3]);
+
+    // a better move at ply 1 with nothing below it
+    table.update(1, moves[4]);
+    table.update(0, moves[3]);
+    assert_eq!(table.line(), &[moves[3], moves[4]]);
+
+    table.clear(0);
+    assert!(table.line().is_empty());
+}
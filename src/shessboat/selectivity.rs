@@ -0,0 +1,171 @@
+use crate::shessboard::{
+    BitBoard,
+    enums::Color,
+    pieces::{Millipawns, P},
+    rules::Rules,
+};
+
+/// Which search techniques are on, so each can be measured.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Selectivity {
+    pub principal_variation: bool,
+    pub aspiration: bool,
+    pub null_move: bool,
+    pub late_move_reductions: bool,
+    pub reverse_futility: bool,
+    pub futility: bool,
+    pub late_move_pruning: bool,
+}
+
+impl Selectivity {
+    /// Plain alpha-beta, searching every move to full depth.
+    pub const NONE: Self = Self {
+        principal_variation: false,
+        aspiration: false,
+        null_move: false,
+        late_move_reductions: false,
+        reverse_futility: false,
+        futility: false,
+        late_move_pruning: false,
+    };
+
+    pub const ALL: Self = Self {
+        principal_variation: true,
+        aspiration: true,
+        null_move: true,
+        late_move_reductions: true,
+        reverse_futility: true,
+        futility: true,
+        late_move_pruning: true,
+    };
+}
+
+impl Selectivity {
+    /// Turns a technique on or off by its short name: `pvs`, `aspiration`,
+    /// `null`, `lmr`, `rfp`, `futility` or `lmp`.
+    pub fn set(&mut self, name: &str, on: bool) -> Result<(), &'static str> {
+        let toggle = match name {
+            "pvs" => &mut self.principal_variation,
+            "aspiration" => &mut self.aspiration,
+            "null" => &mut self.null_move,
+            "lmr" => &mut self.late_move_reductions,
+            "rfp" => &mut self.reverse_futility,
+            "futility" => &mut self.futility,
+            "lmp" => &mut self.late_move_pruning,
+            _ => return Err("No such search technique"),
+        };
+        *toggle = on;
+        Ok(())
+    }
+}
+
+impl Default for Selectivity {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+/// Evaluation margin per ply of depth left for both kinds of futility.
+pub const FUTILITY_MARGIN: Millipawns = 2 * P;
+
+/// Deepest remaining depth at which futility and late-move pruning apply.
+pub const FRONTIER: usize = 3;
+
+/// Quiet moves searched at `depth` before late-move pruning sets in.
+pub const fn late_move_count(depth: usize) -> usize {
+    3 + depth * depth
+}
+
+/// Depth skipped on top of the null move itself.
+pub const fn null_move_reduction(depth: usize) -> usize {
+    2 + depth / 4
+}
+
+/// Plies taken off the `index`th move tried at `depth`. Quiet moves that
+/// have caused cutoffs before are reduced less.
+pub fn late_move_reduction(depth: usize, index: usize, history: Millipawns) -> usize {
+    if depth < 3 || index < 3 {
+        return 0;
+    }
+
+    let mut res = 1 + (index >= 8) as usize + (depth >= 6) as usize;
+    if history > 0 {
+        res -= 1;
+    }
+    res.min(depth - 2)
+}
+
+/// Whether passing could tell anything about `board`. With nothing but
+/// pawns and royals left, zugzwang makes the null move unsound, and some
+/// rules never let passing stand in for moving.
+pub fn null_move_allowed<R: Rules + ?Sized>(rules: &R, board: &BitBoard, color: Color) -> bool {
+    if !rules.null_move_sound() {
+        return false;
+    }
+
+    let half = board.color(color);
+    let royal = half.piece_mask(rules.royal());
+    (half.as_mask() & !half.pawns.as_mask() & !half.kings.as_mask() & !royal).any()
+}
+
+/// `board` with the side to move passing.
+pub fn null_move(board: &BitBoard) -> BitBoard {
+    let mut res = board.clone();
+    res.metadata.to_move = board.metadata.to_move.other();
+    res.metadata.en_passant = None;
+    res.metadata.tempo += 1;
+    res.metadata.half_turns += 1;
+    res
+}
+
+#[test]
+fn null_move_guards() {
+    use crate::shessboard::{
+        enums::Variant,
+        notation::fen::read_fen,
+        rules::Rules,
+        rules::StandardChess,
+        squares::Square,
+        variants::{Antichess, Extinction, Knightmate, Marseillais},
+    };
+
+    let board = BitBoard::new();
+    assert!(null_move_allowed(&StandardChess, &board, Color::White));
+    for rules in [&Antichess as &dyn Rules, &Extinction, &Marseillais] {
+        assert!(!null_move_allowed(rules, &rules.start(), Color::White));
+    }
+
+    let fen = "4k3/4p3/8/8/8/8/3PP3/3NK3 w - - 0 1";
+    let ((board, _), "") = read_fen(Variant::Standard, fen).unwrap() else {
+        panic!()
+    };
+    assert!(null_move_allowed(&StandardChess, &board, Color::White));
+    assert!(!null_move_allowed(&StandardChess, &board, Color::Black));
+
+    let mut board = Knightmate.start();
+    for sq in [
+        Square::b1,
+        Square::c1,
+        Square::d1,
+        Square::f1,
+        Square::a1,
+        Square::h1,
+    ] {
+        board.set_piece(None, sq);
+    }
+    assert!(!null_move_allowed(&Knightmate, &board, Color::White));
+
+    let passed = null_move(&BitBoard::new());
+    assert_eq!(passed.metadata.to_move, Color::Black);
+    assert_eq!(passed.white, BitBoard::new().white);
+
+    let mut selectivity = Selectivity::NONE;
+    selectivity.set("lmr", true).unwrap();
+    assert!(selectivity.late_move_reductions && !selectivity.null_move);
+    assert!(selectivity.set("razoring", true).is_err());
+
+    assert_eq!(late_move_reduction(2, 10, 0), 0);
+    assert_eq!(late_move_reduction(6, 10, 0), 3);
+    assert_eq!(late_move_reduction(6, 10, 5), 2);
+    assert_eq!(late_move_reduction(3, 10, 0), 1);
+}
@@ -0,0 +1,366 @@
+use std::{
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use crate::shessboard::{
+    enums::Color,
+    moves::Move,
+    pieces::{Millipawns, P},
+};
+
+use super::SearchResult;
+
+/// Kept back from every budget for the time it takes to send the move.
+const MOVE_OVERHEAD: Duration = Duration::from_millis(30);
+
+/// Moves the remaining time is spread over when the number is not given.
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+
+/// A score falling at least this much from one iteration to the next buys
+/// more time to look for something better.
+const SCORE_DROP: Millipawns = P / 4;
+
+/// The hard budget is checked this often, in nodes.
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+/// What limits a search, as in the UCI `go` command.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Duration,
+    pub binc: Duration,
+    pub movestogo: Option<u32>,
+    pub movetime: Option<Duration>,
+    pub nodes: Option<u64>,
+    pub depth: Option<usize>,
+}
+
+impl Limits {
+    pub fn depth(depth: usize) -> Self {
+        Self {
+            depth: Some(depth),
+            ..Self::default()
+        }
+    }
+
+    /// Reads UCI `go` arguments such as `wtime 60000 btime 60000 winc 1000`,
+    /// with times in milliseconds, up to the first word it does not know.
+    pub fn read(mut s: &str) -> Option<(Self, &str)> {
+        let mut res = Self::default();
+
+        loop {
+            let rest = s.trim_start();
+            let Some((key, after)) = rest.split_once(char::is_whitespace) else {
+                return Some((res, rest));
+            };
+            let after = after.trim_start();
+            let end = after.find(char::is_whitespace).unwrap_or(after.len());
+            let (value, after) = after.split_at(end);
+            let ms = || value.parse().ok().map(Duration::from_millis);
+
+            match key {
+                "wtime" => res.wtime = Some(ms()?),
+                "btime" => res.btime = Some(ms()?),
+                "winc" => res.winc = ms()?,
+                "binc" => res.binc = ms()?,
+                "movestogo" => res.movestogo = Some(value.parse().ok()?),
+                "movetime" => res.movetime = Some(ms()?),
+                "nodes" => res.nodes = Some(value.parse().ok()?),
+                "depth" => res.depth = Some(value.parse().ok()?),
+                _ => return Some((res, rest)),
+            }
+            s = after;
+        }
+    }
+}
+
+/// Decides when a search for `color` has to stop. No iteration is started
+/// past the soft budget, which grows while the best move keeps changing or
+/// the score is falling, and the search is cut short at the hard budget.
+pub struct TimeManager {
+    start: Instant,
+    soft: Option<Duration>,
+    hard: Option<Duration>,
+    nodes: Option<u64>,
+    instability: f64,
+    score_drop: bool,
+    last: Option<(Option<Move>, Millipawns)>,
+}
+
+impl TimeManager {
+    pub fn new(limits: &Limits, color: Color) -> Self {
+        let (time, inc) = match color {
+            Color::White => (limits.wtime, limits.winc),
+            Color::Black => (limits.btime, limits.binc),
+        };
+
+        let (soft, hard) = if let Some(movetime) = limits.movetime {
+            (Some(movetime), Some(movetime))
+        } else if let Some(time) = time {
+            let left = time.saturating_sub(MOVE_OVERHEAD);
+            let moves = limits.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+            let hard = (left / moves * 4 + inc).min(left);
+            let soft = (left / moves + inc * 3 / 4).min(hard);
+            (Some(soft), Some(hard))
+        } else {
+            (None, None)
+        };
+
+        Self {
+            start: Instant::now(),
+            soft,
+            hard,
+            nodes: limits.nodes,
+            instability: 0.0,
+            score_drop: false,
+            last: None,
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn hard(&self) -> Option<Duration> {
+        self.hard
+    }
+
+    /// The soft budget as adapted to the iterations so far.
+    pub fn soft(&self) -> Option<Duration> {
+        let mut factor = 1.0 + self.instability;
+        if self.score_drop {
+            factor *= 1.5;
+        }
+        Some(self.soft?.mul_f64(factor).min(self.hard?))
+    }
+
+    /// Takes in a finished iteration and tells whether to start another.
+    pub fn next_iteration(&mut self, res: &SearchResult) -> bool {
+        self.next_iteration_after(res, self.elapsed())
+    }
+
+    /// As `next_iteration`, with `elapsed` spent since the search started.
+    fn next_iteration_after(&mut self, res: &SearchResult, elapsed: Duration) -> bool {
+        if let Some((best, score)) = self.last {
+            let changed = best != res.best;
+            self.instability = self.instability / 2.0 + if changed { 1.0 } else { 0.0 };
+            self.score_drop = score - res.score >= SCORE_DROP;
+        }
+        self.last = Some((res.best, res.score));
+
+        self.soft().is_none_or(|soft| elapsed < soft)
+    }
+
+    /// Whether the search has to stop now, after `nodes` nodes.
+    pub fn out_of_budget(&self, nodes: u64) -> bool {
+        self.nodes.is_some_and(|limit| nodes >= limit)
+            || (nodes.is_multiple_of(TIME_CHECK_INTERVAL) && self.past_hard(self.elapsed()))
+    }
+
+    fn past_hard(&self, elapsed: Duration) -> bool {
+        self.hard.is_some_and(|hard| elapsed >= hard)
+    }
+}
+
+/// A chess clock with an increment, for games against the engine.
+pub struct Clock {
+    pub white: Duration,
+    pub black: Duration,
+    pub increment: Duration,
+    time: Duration,
+    turn_start: Instant,
+}
+
+impl Clock {
+    pub fn new(time: Duration, increment: Duration) -> Self {
+        Self {
+            white: time,
+            black: time,
+            increment,
+            time,
+            turn_start: Instant::now(),
+        }
+    }
+
+    pub fn remaining(&self, color: Color) -> Duration {
+        match color {
+            Color::White => self.white,
+            Color::Black => self.black,
+        }
+    }
+
+    pub fn flagged(&self, color: Color) -> bool {
+        self.remaining(color).is_zero()
+    }
+
+    /// Restarts the turn without charging anyone, as after setting up.
+    pub fn restart(&mut self) {
+        self.turn_start = Instant::now();
+    }
+
+    /// Gives both sides their starting time back, for a new game.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.time, self.increment);
+    }
+
+    /// Charges `color` for the turn it just finished and adds the increment.
+    pub fn end_turn(&mut self, color: Color) {
+        let spent = self.turn_start.elapsed();
+        let increment = self.increment;
+        let remaining = match color {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        };
+        *remaining = remaining.saturating_sub(spent);
+        if !remaining.is_zero() {
+            *remaining += increment;
+        }
+        self.restart();
+    }
+
+    pub fn limits(&self) -> Limits {
+        Limits {
+            wtime: Some(self.white),
+            btime: Some(self.black),
+            winc: self.increment,
+            binc: self.increment,
+            ..Limits::default()
+        }
+    }
+}
+
+fn write_duration(d: Duration) -> String {
+    let tenths = d.as_millis() / 100;
+    format!("{}:{:02}.{}", tenths / 600, tenths / 10 % 60, tenths % 10)
+}
+
+impl Display for Clock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} | {}",
+            write_duration(self.white),
+            write_duration(self.black)
+        )
+    }
+}
+
+#[test]
+fn time_budgets() {
+    let (limits, "") = Limits::read("wtime 60030 btime 3030 winc 1000 binc 0").unwrap() else {
+        panic!()
+    };
+    let white = TimeManager::new(&limits, Color::White);
+    assert_eq!(white.soft, Some(Duration::from_millis(2750)));
+    assert_eq!(white.hard, Some(Duration::from_millis(9000)));
+    let black = TimeManager::new(&limits, Color::Black);
+    assert_eq!(black.soft, Some(Duration::from_millis(100)));
+
+    let (limits, "") = Limits::read("wtime 1030 movestogo 1").unwrap() else {
+        panic!()
+    };
+    let last_move = TimeManager::new(&limits, Color::White);
+    assert_eq!(last_move.soft, Some(Duration::from_secs(1)));
+    assert_eq!(last_move.hard, Some(Duration::from_secs(1)));
+
+    let (limits, rest) = Limits::read("movetime 500 nodes 100 depth 7 infinite").unwrap();
+    assert_eq!(rest, "infinite");
+    assert_eq!(limits.movetime, Some(Duration::from_millis(500)));
+    assert_eq!(limits.nodes, Some(100));
+    assert_eq!(limits.depth, Some(7));
+    let fixed = TimeManager::new(&limits, Color::Black);
+    assert_eq!(fixed.soft(), fixed.hard());
+    assert!(!fixed.out_of_budget(99));
+    assert!(fixed.out_of_budget(100));
+
+    assert!(Limits::read("wtime soon").is_none());
+    assert_eq!(Limits::read(""), Some((Limits::default(), "")));
+}
+
+#[test]
+fn adaptive_soft_budget() {
+    use crate::shessboard::BitBoard;
+
+    let mut moves = vec![];
+    BitBoard::new().generate_moves(&mut moves);
+    let result = |best: Move, score: Millipawns| SearchResult {
+        best: Some(best),
+        score,
+        pv: vec![best],
+        depth: 1,
+    };
+
+    let mut manager = TimeManager::new(&Limits::read("wtime 60030").unwrap().0, Color::White);
+    let base = manager.soft().unwrap();
+    assert!(manager.next_iteration(&result(moves[0], 0)));
+    assert_eq!(manager.soft(), Some(base));
+
+    manager.next_iteration(&result(moves[1], 0));
+    let unstable = manager.soft().unwrap();
+    assert!(unstable > base);
+
+    manager.next_iteration(&result(moves[1], -P));
+    assert!(manager.soft().unwrap() > base);
+    manager.next_iteration(&result(moves[1], -P));
+    manager.next_iteration(&result(moves[1], -P));
+    assert!(manager.soft().unwrap() < unstable);
+    assert!(manager.soft() <= manager.hard());
+}
+
+#[test]
+fn budget_decisions() {
+    use crate::shessboard::BitBoard;
+
+    let mut moves = vec![];
+    BitBoard::new().generate_moves(&mut moves);
+    let result = SearchResult {
+        best: Some(moves[0]),
+        score: 0,
+        pv: vec![moves[0]],
+        depth: 1,
+    };
+    let ms = Duration::from_millis;
+
+    let (limits, "") = Limits::read("movetime 100").unwrap() else {
+        panic!()
+    };
+    let mut manager = TimeManager::new(&limits, Color::White);
+    assert!(manager.next_iteration_after(&result, ms(99)));
+    assert!(!manager.next_iteration_after(&result, ms(100)));
+    assert!(!manager.past_hard(ms(99)));
+    assert!(manager.past_hard(ms(100)));
+
+    let (limits, "") = Limits::read("wtime 60030").unwrap() else {
+        panic!()
+    };
+    let mut manager = TimeManager::new(&limits, Color::White);
+    let (soft, hard) = (manager.soft().unwrap(), manager.hard().unwrap());
+    assert!(manager.next_iteration_after(&result, soft - ms(1)));
+    assert!(!manager.next_iteration_after(&result, soft));
+    assert!(!manager.past_hard(soft));
+    assert!(manager.past_hard(hard));
+
+    let unlimited = TimeManager::new(&Limits::depth(5), Color::White);
+    assert!(!unlimited.past_hard(Duration::from_secs(3600)));
+}
+
+#[test]
+fn clock_turns() {
+    let mut clock = Clock::new(Duration::from_secs(60), Duration::from_secs(2));
+    clock.end_turn(Color::White);
+    assert!(clock.white > Duration::from_secs(61));
+    assert!(clock.white <= Duration::from_secs(62));
+    assert_eq!(clock.black, Duration::from_secs(60));
+    assert!(!clock.flagged(Color::White));
+
+    let mut clock = Clock::new(Duration::ZERO, Duration::from_secs(2));
+    clock.end_turn(Color::Black);
+    assert!(clock.flagged(Color::Black));
+    assert_eq!(clock.limits().btime, Some(Duration::ZERO));
+
+    clock.reset();
+    clock.white = Duration::from_millis(61_250);
+    assert_eq!(clock.to_string(), "1:01.2 | 0:00.0");
+}
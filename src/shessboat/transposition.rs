@@ -0,0 +1,178 @@
+use std::mem::size_of;
+
+use crate::shessboard::{moves::Move, pieces::Millipawns, zobrist::HashResult};
+
+use super::is_mate;
+
+/// How a stored score relates to the true value of the position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The search failed high, so the value is at least the score.
+    Lower,
+    /// The search failed low, so the value is at most the score.
+    Upper,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The upper half of the hash, to tell apart positions sharing a bucket.
+    pub key: u32,
+    pub depth: u8,
+    pub bound: Bound,
+    pub age: u8,
+    pub score: Millipawns,
+    pub best: Option<Move>,
+}
+
+/// One slot kept for the deepest search of the bucket and one that always
+/// takes the latest.
+type Bucket = [Option<Entry>; 2];
+
+/// A fixed-size table of searched positions, indexed by Zobrist hash.
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    age: u8,
+}
+
+impl TranspositionTable {
+    pub const DEFAULT_MB: usize = 16;
+
+    pub fn new(mb: usize) -> Self {
+        let len = (mb * 1024 * 1024 / size_of::<Bucket>()).max(1);
+        Self {
+            buckets: vec![[None; 2]; len],
+            age: 0,
+        }
+    }
+
+    pub fn resize(&mut self, mb: usize) {
+        *self = Self::new(mb);
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.fill([None; 2]);
+        self.age = 0;
+    }
+
+    /// Marks what later searches store as newer than what is in the table.
+    pub fn new_search(&mut self) {
+        self.age = self.age.wrapping_add(1);
+    }
+
+    fn index(&self, hash: HashResult) -> usize {
+        (hash % self.buckets.len() as HashResult) as usize
+    }
+
+    fn key(hash: HashResult) -> u32 {
+        (hash >> 32) as u32
+    }
+
+    /// The entry for `hash`, with mate scores counted from `ply`.
+    pub fn probe(&self, hash: HashResult, ply: usize) -> Option<Entry> {
+        let key = Self::key(hash);
+        let mut entry = self.buckets[self.index(hash)]
+            .into_iter()
+            .flatten()
+            .find(|e| e.key == key)?;
+
+        if is_mate(entry.score) {
+            entry.score -= entry.score.signum() * ply as Millipawns;
+        }
+        Some(entry)
+    }
+
+    /// Stores mate scores counted from the position itself rather than the
+    /// root, so they stay right when it is reached at another ply.
+    pub fn store(
+        &mut self,
+        hash: HashResult,
+        ply: usize,
+        depth: usize,
+        bound: Bound,
+        mut score: Millipawns,
+        best: Option<Move>,
+    ) {
+        if is_mate(score) {
+            score += score.signum() * ply as Millipawns;
+        }
+        let entry = Entry {
+            key: Self::key(hash),
+            depth: depth.min(u8::MAX as usize) as u8,
+            bound,
+            age: self.age,
+            score,
+            best,
+        };
+
+        let i = self.index(hash);
+        let bucket = &mut self.buckets[i];
+        let slot = match *bucket {
+            [Some(e), _] if e.key == entry.key => 0,
+            [_, Some(e)] if e.key == entry.key => 1,
+            [None, _] => 0,
+            [Some(e), _] if e.depth <= entry.depth || e.age != entry.age => 0,
+            _ => 1,
+        };
+
+        // keep the best move when an upper bound did not find one
+        let best = entry.best.or(bucket[slot]
+            .filter(|e| e.key == entry.key)
+            .and_then(|e| e.best));
+        bucket[slot] = Some(Entry { best, ..entry });
+    }
+
+    /// How full the table is in permille, counting only entries from the
+    /// current search in the first thousand buckets.
+    pub fn hashfull(&self) -> usize {
+        let sample = &self.buckets[..self.buckets.len().min(1000)];
+        let used = sample
+            .iter()
+            .flatten()
+            .flatten()
+            .filter(|e| e.age == self.age)
+            .count();
+        used * 1000 / (2 * sample.len())
+    }
+}
+
+#[test]
+fn table_entries() {
+    use super::GameEnd;
+
+    let mut table = TranspositionTable::new(1);
+    assert!(table.buckets.len() * size_of::<Bucket>() <= 1024 * 1024);
+    assert_eq!(table.probe(42, 0), None);
+
+    // mate in 3 from the root, found 2 plies in, is mate in 1 from there
+    table.store(42, 2, 5, Bound::Exact, GameEnd::VICTORY - 3, None);
+    assert_eq!(table.probe(42, 2).unwrap().score, GameEnd::VICTORY - 3);
+    assert_eq!(table.probe(42, 0).unwrap().score, GameEnd::VICTORY - 1);
+    table.store(43, 1, 5, Bound::Upper, GameEnd::DEFEAT + 4, None);
+    assert_eq!(table.probe(43, 3).unwrap().score, GameEnd::DEFEAT + 6);
+
+    // positions in the same bucket
+    let len = table.buckets.len() as HashResult;
+    let (a, b, c) = (7, 7 + (len << 32), 7 + (len << 33));
+    table.store(a, 0, 6, Bound::Lower, 100, None);
+    table.store(b, 0, 2, Bound::Exact, 200, None);
+    assert_eq!(table.probe(a, 0).unwrap().depth, 6);
+    assert_eq!(table.probe(b, 0).unwrap().score, 200);
+    table.store(c, 0, 3, Bound::Exact, 300, None);
+    assert_eq!(table.probe(a, 0).unwrap().depth, 6);
+    assert_eq!(table.probe(b, 0), None);
+
+    // the deep slot goes to newer searches
+    table.new_search();
+    table.store(b, 0, 1, Bound::Exact, 200, None);
+    assert_eq!(table.probe(a, 0), None);
+    assert_eq!(table.probe(c, 0).unwrap().score, 300);
+
+    for hash in 0..500 {
+        table.store(hash, 0, 1, Bound::Exact, 0, None);
+    }
+    assert_eq!(table.hashfull(), 250);
+    table.clear();
+    assert_eq!(table.hashfull(), 0);
+    assert_eq!(table.probe(b, 0), None);
+}